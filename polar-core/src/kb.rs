@@ -1,17 +1,20 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{Duration, Instant};
 
 use crate::error::ParameterError;
 use crate::error::{PolarError, PolarResult};
 
 pub use super::bindings::Bindings;
 use super::counter::Counter;
+use super::parser::{parse_lines, Line};
 use super::rules::*;
+use super::source_cache::SourceCache;
 use super::sources::*;
-use super::sugar::Namespaces;
+use super::sugar::{combine_errors, ResourceBlocks};
 use super::terms::*;
 use std::sync::Arc;
 
-enum RuleParamMatch {
+pub(crate) enum RuleParamMatch {
     True,
     False(String),
 }
@@ -45,8 +48,31 @@ pub struct KnowledgeBase {
     id_counter: Counter,
     pub inline_queries: Vec<Term>,
 
-    /// Namespace Bookkeeping
-    pub namespaces: Namespaces,
+    /// Resource blocks parsed so far but not yet rewritten into plain rules. Transient: populated
+    /// by `ResourceBlock::add_to_kb` as blocks are loaded, consumed (and cleared) by
+    /// `rewrite_implications`.
+    pub resource_blocks: ResourceBlocks,
+
+    /// Optional persistent cache of parsed/validated sources, enabled via `with_cache`. When
+    /// unset, caching is a no-op and every source is parsed and validated from scratch.
+    cache: Option<SourceCache>,
+
+    /// Memoized per-rule-name results from `validate_rules_incremental`.
+    validation_cache: HashMap<Symbol, PolarResult<()>>,
+    /// Rule names whose `validation_cache` entry is stale (or was never computed).
+    validation_dirty: HashSet<Symbol>,
+
+    /// Minimum time between two `end_file_reload` calls for the same file that both trigger a
+    /// `validate_rules_incremental` pass. See `end_file_reload`.
+    reload_debounce: Duration,
+    /// Last time each file was reloaded via `end_file_reload`, for debouncing.
+    last_reload: HashMap<String, Instant>,
+
+    /// The transitive role/permission grant closure materialized from the last `rewrite_implications`
+    /// call, keyed by `(resource, term)`. Computed once at load time (`ResourceBlocks::materialize_closure`)
+    /// rather than re-derived from the rewritten `has_role`/`has_permission` rules on every query. See
+    /// `grants_for`.
+    role_closure: HashMap<(Term, Term), HashSet<super::sugar::GrantEdge>>,
 }
 
 impl KnowledgeBase {
@@ -62,10 +88,33 @@ impl KnowledgeBase {
             id_counter: Counter::default(),
             gensym_counter: Counter::default(),
             inline_queries: vec![],
-            namespaces: Namespaces::new(),
+            resource_blocks: ResourceBlocks::new(),
+            cache: None,
+            validation_cache: HashMap::new(),
+            validation_dirty: HashSet::new(),
+            reload_debounce: Duration::default(),
+            last_reload: HashMap::new(),
+            role_closure: HashMap::new(),
         }
     }
 
+    /// Enable a persistent on-disk cache of parsed and validated sources, backed by `path`. Once
+    /// enabled, `add_source` will skip re-parsing any source whose content (and the cache format
+    /// version) it's already seen.
+    pub fn with_cache(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.cache = Some(SourceCache::new(path));
+        self
+    }
+
+    /// Set the minimum time between two `end_file_reload` calls for the same file that both trigger a
+    /// full `validate_rules_incremental` pass (default: no debouncing). Host integrations that
+    /// reload a file on every keystroke (e.g. an editor's "validate on save" watching a file that
+    /// autosaves) should set this to coalesce a burst of reloads into a single revalidation.
+    pub fn with_reload_debounce(mut self, debounce: Duration) -> Self {
+        self.reload_debounce = debounce;
+        self
+    }
+
     /// Return a monotonically increasing integer ID.
     ///
     /// Wraps around at 52 bits of precision so that it can be safely
@@ -99,73 +148,308 @@ impl KnowledgeBase {
     }
 
     pub fn add_rule(&mut self, rule: Rule) {
+        let rule = self.flatten_nested_specializers(rule);
+        let name = rule.name.clone();
         let generic_rule = self
             .rules
-            .entry(rule.name.clone())
+            .entry(name.clone())
             .or_insert_with(|| GenericRule::new(rule.name.clone(), vec![]));
         generic_rule.add_rule(Arc::new(rule));
+        self.mark_validation_dirty(name);
+    }
+
+    /// Rewrite `rule` so that every parameter specializer has a pattern depth of at most 1.
+    ///
+    /// Nested specializers (e.g. `f(x: Foo{owner: Bar{name: "gwen"}})`) aren't handled by
+    /// `param_fields_match`, which only compares specializer fields directly. This mirrors the
+    /// definition-flattening technique used by pattern-match compilers: whenever a field value
+    /// inside a specializer is itself a pattern, hoist it out into a fresh `gensym`'d variable and
+    /// append a `var matches NestedPattern` constraint to the rule's body instead. The rewrite
+    /// recurses until no field value is a pattern, so `rule_params_match` only ever has to compare
+    /// flat heads.
+    fn flatten_nested_specializers(&self, mut rule: Rule) -> Rule {
+        let mut extra_constraints = vec![];
+        for param in &mut rule.params {
+            if let Some(specializer) = param.specializer.take() {
+                let new_specializer = match specializer.value().clone() {
+                    Value::Pattern(pattern) => {
+                        let flattened = self.flatten_pattern(pattern, &mut extra_constraints);
+                        specializer.clone_with_value(value!(Value::Pattern(flattened)))
+                    }
+                    _ => specializer,
+                };
+                param.specializer = Some(new_specializer);
+            }
+        }
+        if !extra_constraints.is_empty() {
+            rule.body = self.append_constraints(rule.body, extra_constraints);
+        }
+        rule
+    }
+
+    /// Hoist nested patterns out of `pattern`'s fields, recording a `matches` constraint for each
+    /// one hoisted. Value specializers (strings, numbers, lists, booleans) are left untouched.
+    fn flatten_pattern(&self, pattern: Pattern, extra: &mut Vec<Term>) -> Pattern {
+        match pattern {
+            Pattern::Instance(InstanceLiteral { tag, fields }) => {
+                Pattern::Instance(InstanceLiteral {
+                    tag,
+                    fields: self.flatten_fields(fields, extra),
+                })
+            }
+            Pattern::Dictionary(fields) => Pattern::Dictionary(self.flatten_fields(fields, extra)),
+        }
+    }
+
+    fn flatten_fields(&self, fields: Dictionary, extra: &mut Vec<Term>) -> Dictionary {
+        let fields = fields
+            .fields
+            .into_iter()
+            .map(|(k, v)| (k, self.flatten_field_value(v, extra)))
+            .collect::<BTreeMap<_, _>>();
+        Dictionary { fields }
+    }
+
+    /// If `value` is itself a (possibly nested) pattern, replace it with a fresh variable and push
+    /// a `var matches <flattened pattern>` constraint onto `extra`. Otherwise, leave it alone.
+    fn flatten_field_value(&self, value: Term, extra: &mut Vec<Term>) -> Term {
+        match value.value().clone() {
+            Value::Pattern(nested) => {
+                // Recurse first so deeply nested patterns are flattened bottom-up, then hoist.
+                let nested = self.flatten_pattern(nested, extra);
+                let var = self.gensym("specializer");
+                let var_term = value.clone_with_value(value!(var));
+                let pattern_term = value.clone_with_value(value!(Value::Pattern(nested)));
+                extra.push(value.clone_with_value(value!(Value::Expression(Operation {
+                    operator: Operator::Isa,
+                    args: vec![var_term.clone(), pattern_term],
+                }))));
+                var_term
+            }
+            _ => value,
+        }
+    }
+
+    /// Append `extra` constraints to `body`, folding into the existing top-level `And` if there is
+    /// one, or wrapping `body` in a fresh `And` otherwise.
+    fn append_constraints(&self, body: Term, mut extra: Vec<Term>) -> Term {
+        match body.value().clone() {
+            Value::Expression(Operation {
+                operator: Operator::And,
+                mut args,
+            }) => {
+                args.append(&mut extra);
+                body.clone_with_value(value!(Value::Expression(Operation {
+                    operator: Operator::And,
+                    args,
+                })))
+            }
+            _ => {
+                let mut args = vec![body.clone()];
+                args.append(&mut extra);
+                body.clone_with_value(value!(Value::Expression(Operation {
+                    operator: Operator::And,
+                    args,
+                })))
+            }
+        }
     }
 
     /// Validate that all rules loaded into the knowledge base are valid based on rule prototypes.
+    /// Checks every rule name rather than bailing out at the first invalid one, so a single
+    /// `validate_rules` call surfaces every prototype mismatch in the policy at once.
     pub fn validate_rules(&self) -> PolarResult<()> {
-        for (rule_name, generic_rule) in &self.rules {
-            if let Some(prototypes) = self.rule_prototypes.get(rule_name) {
-                // If a prototype with the same name exists, then the parameters must match for each rule
-                for rule in generic_rule.rules.values() {
-                    let mut msg = "Must match one of the following rule prototypes:\n".to_owned();
-
-                    let found_match = prototypes
-                        .iter()
-                        .map(|prototype| {
-                            self.rule_params_match(rule.as_ref(), prototype)
-                                .map(|result| (result, prototype))
+        let errors: Vec<PolarError> = self
+            .rules
+            .keys()
+            .filter_map(|rule_name| self.validate_rule_name(rule_name).err())
+            .collect();
+        if !errors.is_empty() {
+            return Err(combine_errors(errors));
+        }
+        Ok(())
+    }
+
+    /// Validate just the rules registered under `rule_name` against their prototypes (if any).
+    /// Factored out of `validate_rules` so both the full recompute and `validate_rules_incremental`
+    /// share the same per-name check.
+    fn validate_rule_name(&self, rule_name: &Symbol) -> PolarResult<()> {
+        let generic_rule = match self.rules.get(rule_name) {
+            Some(generic_rule) => generic_rule,
+            None => return Ok(()),
+        };
+        if let Some(prototypes) = self.rule_prototypes.get(rule_name) {
+            // If a prototype with the same name exists, then the parameters must match for each rule
+            for rule in generic_rule.rules.values() {
+                let mut msg = "Must match one of the following rule prototypes:\n".to_owned();
+
+                let found_match = prototypes
+                    .iter()
+                    .map(|prototype| {
+                        self.rule_params_match(rule.as_ref(), prototype)
+                            .map(|result| (result, prototype))
+                    })
+                    .collect::<PolarResult<Vec<(RuleParamMatch, &Rule)>>>()
+                    .map(|results| {
+                        results.iter().any(|(result, prototype)| match result {
+                            RuleParamMatch::True => true,
+                            RuleParamMatch::False(message) => {
+                                msg.push_str(&format!(
+                                    "\n{}\n\tFailed to match because: {}\n",
+                                    prototype.to_polar(),
+                                    message
+                                ));
+                                false
+                            }
                         })
-                        .collect::<PolarResult<Vec<(RuleParamMatch, &Rule)>>>()
-                        .map(|results| {
-                            results.iter().any(|(result, prototype)| match result {
-                                RuleParamMatch::True => true,
-                                RuleParamMatch::False(message) => {
-                                    msg.push_str(&format!(
-                                        "\n{}\n\tFailed to match because: {}\n",
-                                        prototype.to_polar(),
-                                        message
-                                    ));
-                                    false
-                                }
-                            })
-                        })?;
-                    if !found_match {
-                        return Err(self.set_error_context(
-                            &rule.body,
-                            error::ValidationError::InvalidRule {
-                                rule: rule.to_polar(),
-                                msg,
-                            },
-                        ));
-                    }
+                    })?;
+                if !found_match {
+                    return Err(self.set_error_context(
+                        &rule.body,
+                        error::ValidationError::InvalidRule {
+                            rule: rule.to_polar(),
+                            msg,
+                        },
+                    ));
                 }
             }
         }
         Ok(())
     }
 
+    /// Incrementally re-validate only the rule names whose prototypes or rules have changed since
+    /// the last call, memoizing the rest. `add_rule`, `add_rule_prototype`, and `remove_source`
+    /// mark the names they touch dirty; everything else reuses its cached `PolarResult<()>`. This
+    /// keeps interactive, reload-on-edit validation cheap for large policies, where a single edit
+    /// would otherwise force an O(rules × prototypes) recheck of the whole knowledge base.
+    pub fn validate_rules_incremental(&mut self) -> PolarResult<()> {
+        let dirty = std::mem::take(&mut self.validation_dirty);
+        for name in dirty {
+            let result = self.validate_rule_name(&name);
+            self.validation_cache.insert(name, result);
+        }
+        for result in self.validation_cache.values() {
+            result.clone()?;
+        }
+        Ok(())
+    }
+
+    /// Mark `name`'s cached validation result (if any) as stale, so the next
+    /// `validate_rules_incremental` recomputes it instead of trusting the cache.
+    fn mark_validation_dirty(&mut self, name: Symbol) {
+        self.validation_cache.remove(&name);
+        self.validation_dirty.insert(name);
+    }
+
     /// Determine whether the fields of a rule parameter specializer match the fields of a prototype parameter specializer.
-    /// Rule fields match if they are a superset of prototype fields and all field values are equal.
-    // TODO: once field-level specializers are working this should be updated so
-    // that it recursively checks all fields match, rather than checking for
-    // equality
+    /// Rule fields match if they are a superset of prototype fields and all overlapping field
+    /// values unify (see `unify_specializer`).
     fn param_fields_match(&self, prototype_fields: &Dictionary, rule_fields: &Dictionary) -> bool {
-        return prototype_fields
-            .fields
-            .iter()
-            .map(|(k, prototype_value)| {
-                rule_fields
-                    .fields
-                    .get(k)
-                    .map(|rule_value| rule_value == prototype_value)
-                    .unwrap_or_else(|| false)
+        let mut bindings = Bindings::new();
+        self.unify_dict(prototype_fields, rule_fields, &mut bindings)
+    }
+
+    /// Structurally unify a prototype specializer term against a rule specializer term.
+    ///
+    /// - Two concrete values unify iff they're equal.
+    /// - A `Value::Variable` on either side unifies with anything; the first time it's seen it
+    ///   records a binding in `bindings`, and if it's already bound, the new term must unify with
+    ///   its prior binding.
+    /// - `Dictionary`s unify field-wise: `rule` must be a superset of `prototype`'s keys, and
+    ///   overlapping values must unify recursively.
+    /// - `List`s unify element-wise (see `unify_list`).
+    /// - Nested `Pattern::Instance`s unify when tags match (or `rule`'s tag is a registered
+    ///   subclass of `prototype`'s tag, i.e. `prototype`'s class appears in `rule`'s tag's MRO)
+    ///   and all fields unify recursively.
+    fn unify_specializer(&self, prototype: &Term, rule: &Term, bindings: &mut Bindings) -> bool {
+        match (prototype.value(), rule.value()) {
+            (Value::Variable(_), _) => self.unify_var(prototype, rule, bindings),
+            (_, Value::Variable(_)) => self.unify_var(rule, prototype, bindings),
+            (Value::Dictionary(p), Value::Dictionary(r)) => self.unify_dict(p, r, bindings),
+            (Value::List(p), Value::List(r)) => self.unify_list(p, r, bindings),
+            (
+                Value::Pattern(Pattern::Instance(p)),
+                Value::Pattern(Pattern::Instance(r)),
+            ) => {
+                let tags_match = p.tag == r.tag
+                    || self
+                        .mro
+                        .get(&r.tag)
+                        .zip(self.constants.get(&p.tag).map(Term::value))
+                        .map_or(false, |(rule_mro, prototype_value)| {
+                            matches!(
+                                prototype_value,
+                                Value::ExternalInstance(ExternalInstance { instance_id, .. })
+                                    if rule_mro.contains(instance_id)
+                            )
+                        });
+                tags_match && self.unify_dict(&p.fields, &r.fields, bindings)
+            }
+            (Value::Pattern(Pattern::Dictionary(p)), Value::Pattern(Pattern::Dictionary(r))) => {
+                self.unify_dict(p, r, bindings)
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    /// Unify a variable (`var_term`) with an arbitrary term, threading bindings through so that a
+    /// variable bound earlier in the same unification must unify with its prior binding, not just
+    /// be overwritten.
+    fn unify_var(&self, var_term: &Term, other: &Term, bindings: &mut Bindings) -> bool {
+        let var = var_term.value().as_symbol().expect("variable").clone();
+        if let Some(bound) = bindings.get(&var).cloned() {
+            self.unify_specializer(&bound, other, bindings)
+        } else {
+            bindings.insert(var, other.clone());
+            true
+        }
+    }
+
+    /// `rule` unifies with `prototype` iff every field in `prototype` is present in `rule` and the
+    /// corresponding values unify. `rule` may have additional fields not present in `prototype`.
+    fn unify_dict(&self, prototype: &Dictionary, rule: &Dictionary, bindings: &mut Bindings) -> bool {
+        prototype.fields.iter().all(|(k, prototype_value)| {
+            rule.fields
+                .get(k)
+                .map_or(false, |rule_value| {
+                    self.unify_specializer(prototype_value, rule_value, bindings)
+                })
+        })
+    }
+
+    /// Unify two lists element-wise. Mirrors the prior containment-based semantics (every
+    /// prototype element must unify with some rule element) while allowing each element
+    /// comparison to recurse and bind variables; a tentative binding set is only committed once a
+    /// match for that prototype element is found.
+    fn unify_list(&self, prototype: &[Term], rule: &[Term], bindings: &mut Bindings) -> bool {
+        prototype.iter().all(|prototype_elem| {
+            rule.iter().any(|rule_elem| {
+                let mut candidate = bindings.clone();
+                if self.unify_specializer(prototype_elem, rule_elem, &mut candidate) {
+                    *bindings = candidate;
+                    true
+                } else {
+                    false
+                }
             })
-            .all(|v| v);
+        })
+    }
+
+    /// Is the class registered as `sub` equal to or a registered subclass of `ancestor`, per the
+    /// MRO? Used wherever a tag needs to match not just itself but anything that would satisfy it
+    /// as a specializer -- e.g. `check_pattern_param` below, and `refactor`'s type-constrained
+    /// search template placeholders.
+    pub(crate) fn is_subclass_or_equal(&self, sub: &Symbol, ancestor: &Symbol) -> bool {
+        if sub == ancestor {
+            return true;
+        }
+        let ancestor_id = match self.constants.get(ancestor).map(Term::value) {
+            Some(Value::ExternalInstance(ExternalInstance { instance_id, .. })) => instance_id,
+            _ => return false,
+        };
+        self.mro
+            .get(sub)
+            .map_or(false, |sub_mro| sub_mro.contains(ancestor_id))
     }
 
     /// Check that a rule parameter that has a pattern specializer matches a prototype parameter that has a pattern specializer.
@@ -188,32 +472,22 @@ impl KnowledgeBase {
                         RuleParamMatch::False(format!("Rule specializer {} on parameter {} did not match prototype specializer {} because the specializer fields did not match.", rule_instance.to_polar(), index, prototype_instance.to_polar()))
                     }
                 // If tags don't match, then rule specializer must be a subclass of prototype specializer
-                } else if let Some(Value::ExternalInstance(ExternalInstance {
-                    instance_id,
-                    ..
-                })) = self
-                    .constants
-                    .get(&prototype_instance.tag)
-                    .map(|t| t.value())
-                {
-                    if let Some(rule_mro) = self.mro.get(&rule_instance.tag) {
-                        if !rule_mro.contains(instance_id) {
-                            RuleParamMatch::False(format!("Rule specializer {} on parameter {} must be a subclass of prototype specializer {}", rule_instance.tag,index, prototype_instance.tag))
-
-                        } else if !self.param_fields_match(
-                                &prototype_instance.fields,
-                                &rule_instance.fields,
-                            )
-                        {
-                            RuleParamMatch::False(format!("Rule specializer {} on parameter {} did not match prototype specializer {} because the specializer fields did not match.", rule_instance.to_polar(), index, prototype_instance.to_polar()))
-                        } else {
-                            RuleParamMatch::True
-                        }
-                    } else {
+                } else if self.constants.contains_key(&prototype_instance.tag) {
+                    if !self.mro.contains_key(&rule_instance.tag) {
                         return Err(error::OperationalError::InvalidState(format!(
                                 "All registered classes must have a registered MRO. Class {} does not have a registered MRO.",
                                 &rule_instance.tag
                             )).into());
+                    } else if !self.is_subclass_or_equal(&rule_instance.tag, &prototype_instance.tag) {
+                        RuleParamMatch::False(format!("Rule specializer {} on parameter {} must be a subclass of prototype specializer {}", rule_instance.tag,index, prototype_instance.tag))
+                    } else if !self.param_fields_match(
+                            &prototype_instance.fields,
+                            &rule_instance.fields,
+                        )
+                    {
+                        RuleParamMatch::False(format!("Rule specializer {} on parameter {} did not match prototype specializer {} because the specializer fields did not match.", rule_instance.to_polar(), index, prototype_instance.to_polar()))
+                    } else {
+                        RuleParamMatch::True
                     }
                 } else {
                     unreachable!("Unregistered specializer classes should be caught before this point.");
@@ -261,7 +535,8 @@ impl KnowledgeBase {
     ) -> PolarResult<RuleParamMatch> {
         Ok(match (prototype_value, rule_value) {
             (Value::List(prototype_list), Value::List(rule_list)) => {
-                if prototype_list.iter().all(|t| rule_list.contains(t)) {
+                let mut bindings = Bindings::new();
+                if self.unify_list(prototype_list, rule_list, &mut bindings) {
                     RuleParamMatch::True
                 } else {
                     RuleParamMatch::False(format!(
@@ -400,7 +675,7 @@ impl KnowledgeBase {
     }
 
     /// Determine whether a rule matches a rule prototype based on its parameters.
-    fn rule_params_match(&self, rule: &Rule, prototype: &Rule) -> PolarResult<RuleParamMatch> {
+    pub(crate) fn rule_params_match(&self, rule: &Rule, prototype: &Rule) -> PolarResult<RuleParamMatch> {
         if rule.params.len() != prototype.params.len() {
             return Ok(RuleParamMatch::False(format!(
                 "Different number of parameters. Rule has {} parameter(s) but prototype has {}.",
@@ -444,11 +719,38 @@ impl KnowledgeBase {
         self.rules.get(name)
     }
 
+    pub(crate) fn get_generic_rule_mut(&mut self, name: &Symbol) -> Option<&mut GenericRule> {
+        self.rules.get_mut(name)
+    }
+
+    /// Find every loaded rule whose head and body structurally match `template`, a Polar rule
+    /// whose variables act as meta-variables (see `refactor` module docs).
+    pub fn search_rules(&self, template: &str) -> PolarResult<Vec<super::refactor::RuleMatch>> {
+        super::refactor::search_rules(self, template)
+    }
+
+    /// Replace every rule matching `search` with `replace`, substituting `search`'s captured
+    /// meta-variable bindings into `replace`. Returns the number of rules rewritten.
+    pub fn replace_rules(&mut self, search: &str, replace: &str) -> PolarResult<usize> {
+        super::refactor::replace_rules(self, search, replace)
+    }
+
+    /// Run a batch of search/replace rewrites in order, for bulk policy audits that need to apply
+    /// many mechanical rewrites in one pass. Returns the total number of rules rewritten.
+    pub fn apply_rewrites(&mut self, rewrites: &[(&str, &str)]) -> PolarResult<usize> {
+        super::refactor::apply_rewrites(self, rewrites)
+    }
+
     pub fn add_rule_prototype(&mut self, prototype: Rule) {
+        let prototype = self.flatten_nested_specializers(prototype);
         let name = prototype.name.clone();
         // get rule prototypes
-        let prototypes = self.rule_prototypes.entry(name).or_insert_with(Vec::new);
+        let prototypes = self
+            .rule_prototypes
+            .entry(name.clone())
+            .or_insert_with(Vec::new);
         prototypes.push(prototype);
+        self.mark_validation_dirty(name);
     }
 
     /// Define a constant variable.
@@ -467,11 +769,64 @@ impl KnowledgeBase {
         Ok(())
     }
 
+    /// Append `ancestor_instance_id` to `name`'s existing MRO list (starting a new one if it has
+    /// none yet), rather than replacing it outright like `add_mro` does. Used to make an
+    /// already-registered class a subclass of a synthesized union type -- e.g. the generated
+    /// `Actor`/`Resource` unions in `sugar::register_union_types` -- without disturbing whatever
+    /// MRO it already has.
+    pub(crate) fn extend_mro(&mut self, name: &Symbol, ancestor_instance_id: u64) -> PolarResult<()> {
+        self.constants.get(name).ok_or_else(|| {
+            ParameterError(format!("Cannot extend MRO for unregistered class {}", name))
+        })?;
+        self.mro.entry(name.clone()).or_default().push(ancestor_instance_id);
+        Ok(())
+    }
+
     /// Return true if a constant with the given name has been defined.
     pub fn is_constant(&self, name: &Symbol) -> bool {
         self.constants.contains_key(name)
     }
 
+    /// Begin reloading `filename`: remove its previous source, which removes all the rules it
+    /// contributed and marks every rule name it touched dirty (per `remove_source`). The caller is
+    /// expected to reparse `filename`'s new contents and add its rules back via
+    /// `add_source`/`add_rule` as usual, then call `end_file_reload` to revalidate.
+    pub fn begin_file_reload(&mut self, filename: &str) {
+        self.remove_file(filename);
+    }
+
+    /// Finish reloading `filename`, started with `begin_file_reload`: run
+    /// `validate_rules_incremental` to surface any validation errors the new content introduced --
+    /// unless this file was last reloaded less than `reload_debounce` ago, in which case the
+    /// revalidation is skipped. The new rules are live either way (`begin_file_reload` already
+    /// removed the stale ones, and the caller already re-added the new ones); only the validation
+    /// pass itself is debounced. In a burst of rapid reloads (e.g. from an editor autosaving
+    /// mid-edit), the first reload validates and each later one that lands within
+    /// `reload_debounce` of the reload before it skips validation, so the burst's final content
+    /// stays unvalidated (its rule names stay dirty) until a later reload of this file arrives
+    /// after the window has passed, or `validate_rules_incremental` is called directly.
+    pub fn end_file_reload(&mut self, filename: &str) -> PolarResult<()> {
+        let now = Instant::now();
+        let should_validate = self
+            .last_reload
+            .get(filename)
+            .map_or(true, |last| now.duration_since(*last) >= self.reload_debounce);
+        self.last_reload.insert(filename.to_owned(), now);
+
+        if should_validate {
+            self.validate_rules_incremental()?;
+        }
+        Ok(())
+    }
+
+    /// Register `source` with the KB and load the rules and rule prototypes it contains. This is
+    /// the sole place a `Source`'s text is parsed into rules: nothing else in this crate calls
+    /// `add_rule`/`add_rule_prototype` from parsed source text, so callers must route all source
+    /// loading through here rather than separately parsing and adding the same content themselves.
+    /// On a cache hit (see `with_cache`), those are replayed from the cache via
+    /// `adopt_cached_source` without re-parsing or re-validating; on a miss, `source` is parsed and
+    /// validated as usual and the outcome is written back to the cache via
+    /// `parse_validate_and_cache_source` so the next identical `add_source` is a hit.
     pub fn add_source(&mut self, source: Source) -> PolarResult<u64> {
         let src_id = self.new_id();
         if let Some(ref filename) = source.filename {
@@ -480,10 +835,145 @@ impl KnowledgeBase {
                 .insert(source.src.clone(), filename.to_string());
             self.loaded_files.insert(filename.to_string(), src_id);
         }
+        let cached = self
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.get(&source.src));
+        let source_for_parsing = source.clone();
         self.sources.add_source(source, src_id);
+        match cached {
+            Some(cached) => self.adopt_cached_source(src_id, cached)?,
+            None => self.parse_validate_and_cache_source(src_id, &source_for_parsing)?,
+        }
         Ok(src_id)
     }
 
+    /// On a cache miss in `add_source`, parse `source` into rules and rule prototypes, add them to
+    /// the KB, validate just the rule names `source` introduced, and write the outcome back to the
+    /// cache (if one is configured) via `cache_parsed_source` so the next identical `add_source`
+    /// can skip straight to `adopt_cached_source` instead of repeating this work.
+    ///
+    /// Validation is scoped to `source`'s own rule names (via `validate_rule_name`) rather than
+    /// `validate_rules` over the whole KB: the cache is keyed only on this source's own content
+    /// hash, so caching a whole-KB validation result would have loading file A alone (passes) and
+    /// loading A after a conflicting file B (fails) race to overwrite the same cache entry with
+    /// whichever outcome happened to run first, and replay it for every future load of A's text
+    /// regardless of what else is loaded alongside it.
+    ///
+    /// A resource block line is dispatched to `ResourceBlock::add_to_kb` immediately rather than
+    /// being collected, since resource blocks aren't part of the cached rules/prototypes; any
+    /// other kind of line is an explicit error rather than a silent no-op, since silently dropping
+    /// it would make its content vanish from the KB with no indication why.
+    fn parse_validate_and_cache_source(&mut self, src_id: u64, source: &Source) -> PolarResult<()> {
+        let mut rules = vec![];
+        let mut rule_prototypes = vec![];
+        for line in parse_lines(src_id, &source.src)? {
+            match line {
+                Line::Rule(rule) => rules.push(rule),
+                Line::RuleType(prototype) => rule_prototypes.push(prototype),
+                Line::ResourceBlock(block) => block.add_to_kb(self)?,
+                _ => {
+                    return Err(error::OperationalError::InvalidState(
+                        "add_source doesn't support this kind of line; only rules, rule \
+                         prototypes, and resource blocks can be loaded via add_source"
+                            .to_owned(),
+                    )
+                    .into());
+                }
+            }
+        }
+        for rule in &rules {
+            self.add_rule(rule.clone());
+        }
+        for prototype in &rule_prototypes {
+            self.add_rule_prototype(prototype.clone());
+        }
+        let touched_names: HashSet<Symbol> = rules
+            .iter()
+            .chain(rule_prototypes.iter())
+            .map(|rule| rule.name.clone())
+            .collect();
+        let errors: Vec<PolarError> = touched_names
+            .iter()
+            .filter_map(|name| self.validate_rule_name(name).err())
+            .collect();
+        let validation_error = if errors.is_empty() {
+            None
+        } else {
+            Some(combine_errors(errors).to_string())
+        };
+        self.cache_parsed_source(source, rules, rule_prototypes, validation_error.clone());
+        match validation_error {
+            Some(msg) => Err(error::ValidationError::InvalidRule {
+                rule: "<loaded>".to_owned(),
+                msg,
+            }
+            .into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Reinsert a cache hit's rules and rule prototypes into `rules`/`rule_prototypes`, rebinding
+    /// their `SourceInfo` to the freshly allocated `src_id` so `remove_source` stays correct, and
+    /// replay its cached validation outcome without re-running `validate_rules`.
+    fn adopt_cached_source(
+        &mut self,
+        src_id: u64,
+        cached: super::source_cache::CachedSource,
+    ) -> PolarResult<()> {
+        let super::source_cache::CachedSource {
+            rules,
+            rule_prototypes,
+            validation_error,
+        } = cached;
+        for mut rule in rules {
+            rule.source_info = SourceInfo::Parser {
+                src_id,
+                left: 0,
+                right: 0,
+            };
+            self.add_rule(rule);
+        }
+        for mut prototype in rule_prototypes {
+            prototype.source_info = SourceInfo::Parser {
+                src_id,
+                left: 0,
+                right: 0,
+            };
+            self.add_rule_prototype(prototype);
+        }
+        if let Some(msg) = validation_error {
+            return Err(error::ValidationError::InvalidRule {
+                rule: "<cached>".to_owned(),
+                msg,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Record the outcome of parsing and validating `source` into the persistent cache (if one is
+    /// configured), so the next `add_source` with identical content can skip straight to
+    /// `adopt_cached_source` instead of re-parsing and re-validating.
+    pub fn cache_parsed_source(
+        &self,
+        source: &Source,
+        rules: Vec<Rule>,
+        rule_prototypes: Vec<Rule>,
+        validation_error: Option<String>,
+    ) {
+        if let Some(cache) = &self.cache {
+            cache.put(
+                &source.src,
+                super::source_cache::CachedSource {
+                    rules,
+                    rule_prototypes,
+                    validation_error,
+                },
+            );
+        }
+    }
+
     pub fn clear_rules(&mut self) {
         self.rules.clear();
         self.rule_prototypes.clear();
@@ -491,6 +981,9 @@ impl KnowledgeBase {
         self.inline_queries.clear();
         self.loaded_content.clear();
         self.loaded_files.clear();
+        self.validation_cache.clear();
+        self.validation_dirty.clear();
+        self.last_reload.clear();
     }
 
     /// Removes a file from the knowledge base by finding the associated
@@ -511,7 +1004,8 @@ impl KnowledgeBase {
     /// also remove the loaded files if the source was loaded from a file.
     pub fn remove_source(&mut self, source_id: u64) -> String {
         // remove from rules
-        self.rules.retain(|_, gr| {
+        let mut touched_names = vec![];
+        self.rules.retain(|name, gr| {
             let to_remove: Vec<u64> = gr.rules.iter().filter_map(|(idx, rule)| {
                 if matches!(rule.source_info, SourceInfo::Parser { src_id, ..} if src_id == source_id) {
                     Some(*idx)
@@ -520,11 +1014,17 @@ impl KnowledgeBase {
                 }
             }).collect();
 
+            if !to_remove.is_empty() {
+                touched_names.push(name.clone());
+            }
             for idx in to_remove {
                 gr.remove_rule(idx);
             }
             !gr.rules.is_empty()
         });
+        for name in touched_names {
+            self.mark_validation_dirty(name);
+        }
 
         // remove from sources
         let source = self
@@ -591,29 +1091,45 @@ impl KnowledgeBase {
         let mut errors = vec![];
 
         errors.append(&mut super::sugar::check_all_relation_types_have_been_registered(self));
+        errors.append(&mut super::sugar::check_for_relation_cycles(&mut self.resource_blocks));
 
-        // TODO(gj): Emit all errors instead of just the first.
         if !errors.is_empty() {
-            self.namespaces.clear();
-            return Err(errors[0].clone());
+            self.resource_blocks.clear();
+            return Err(combine_errors(errors));
+        }
+
+        // Synthesize and register the `Actor`/`Resource` union types before rewriting any
+        // implications, since the rewritten rules' actor parameter specializes against the
+        // `Actor` union rather than a single concrete class.
+        let actors = self.resource_blocks.actors.clone();
+        let resources = self.resource_blocks.resources.clone();
+        if let Err(error) = super::sugar::register_union_types(self, &actors, &resources) {
+            self.resource_blocks.clear();
+            return Err(error);
         }
 
         let mut rules = vec![];
-        for (namespace, implications) in &self.namespaces.implications {
+        for (resource, implications) in &self.resource_blocks.implications {
             for implication in implications {
-                match implication.as_rule(namespace, &self.namespaces) {
+                match implication.as_rule(resource, &self.resource_blocks) {
                     Ok(rule) => rules.push(rule),
                     Err(error) => errors.push(error),
                 }
             }
         }
 
-        // If we've reached this point, we're all done with the namespaces.
-        self.namespaces.clear();
+        // Precompute the transitive role/permission grant closure so hosts can answer "what
+        // grants this?" via `grants_for` without re-walking the rewritten rules on every query.
+        // Must happen before `self.resource_blocks.clear()` below, since that's what it's computed
+        // from.
+        self.resource_blocks.materialize_closure();
+        self.role_closure = self.resource_blocks.take_closure();
+
+        // If we've reached this point, we're all done with the resource blocks.
+        self.resource_blocks.clear();
 
-        // TODO(gj): Emit all errors instead of just the first.
         if !errors.is_empty() {
-            return Err(errors[0].clone());
+            return Err(combine_errors(errors));
         }
 
         // Add the rewritten rules to the KB.
@@ -623,6 +1139,21 @@ impl KnowledgeBase {
 
         Ok(())
     }
+
+    /// Expose the rules each loaded resource block expands into, for introspection tooling (e.g. a
+    /// host binding printing "this `resource Repo` block expands to these N rules"). Note that
+    /// `rewrite_implications` clears `self.resource_blocks` once it's done with them, so this only
+    /// returns anything useful if called before `rewrite_implications`.
+    pub fn desugar_resource_blocks(&self) -> PolarResult<Vec<super::sugar::ResourceBlockExpansion>> {
+        self.resource_blocks.desugar()
+    }
+
+    /// Look up every role/permission (local or reached across a `relation`) that grants `term` on
+    /// `resource`, from the closure `rewrite_implications` precomputed at load time. Returns `None`
+    /// if `rewrite_implications` hasn't run yet, or if `(resource, term)` grants nothing.
+    pub fn grants_for(&self, resource: &Term, term: &Term) -> Option<&HashSet<super::sugar::GrantEdge>> {
+        self.role_closure.get(&(resource.clone(), term.clone()))
+    }
 }
 
 #[cfg(test)]
@@ -1052,4 +1583,27 @@ mod tests {
         kb.add_rule_prototype(rule!("f", ["x"; instance!(sym!("Fruit"))]));
         kb.add_rule(rule!("f", ["x"; instance!(sym!("Fruit"))]));
     }
+
+    #[test]
+    fn test_file_reload_debounce() {
+        let mut kb = KnowledgeBase::new().with_reload_debounce(Duration::from_secs(60));
+        let policy_source = |src: &str| Source {
+            filename: Some("policy.polar".to_owned()),
+            src: src.to_owned(),
+        };
+
+        kb.begin_file_reload("policy.polar");
+        kb.add_source(policy_source("f(1);")).unwrap();
+        kb.end_file_reload("policy.polar").unwrap();
+        assert!(kb.validation_dirty.is_empty());
+
+        // A reload that lands inside the debounce window takes effect immediately (the new rule
+        // is live) but doesn't trigger its own validation pass, leaving the dirtied name
+        // uncleared until a reload outside the window finally revalidates it.
+        kb.begin_file_reload("policy.polar");
+        kb.add_source(policy_source("f(2);")).unwrap();
+        kb.end_file_reload("policy.polar").unwrap();
+        assert!(!kb.validation_dirty.is_empty());
+        assert_eq!(kb.get_generic_rule(&sym!("f")).unwrap().rules.len(), 1);
+    }
 }
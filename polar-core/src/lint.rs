@@ -0,0 +1,275 @@
+//! Policy-lint diagnostics over loaded rules: duplicate and subsumed rules are legal Polar but are
+//! almost always bugs (a copy-pasted rule, or a rule that can never fire because another,
+//! equally-or-more-general one already handles every case it does), so they're worth surfacing as
+//! non-fatal diagnostics rather than silently accepted.
+
+use std::collections::HashMap;
+
+use super::kb::{KnowledgeBase, RuleParamMatch};
+use super::refactor::{source_span, SourceSpan};
+use super::rules::{Parameter, Rule};
+use super::terms::*;
+
+/// A single lint finding from `lint_rules`. Carries enough to point a user at both rules involved
+/// -- `name` plus the two `GenericRule`-local rule indices -- so a caller can render source
+/// locations from them without `lint_rules` needing to know anything more about source spans
+/// itself, plus the redundant rule's own `span` (see `refactor::source_span`) so it can be
+/// reported directly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintDiagnostic {
+    /// Two rules under `name` have identical parameters and bodies up to renaming of variables:
+    /// `rule_idx` adds nothing `duplicate_of_idx` doesn't already provide.
+    DuplicateRule {
+        name: Symbol,
+        rule_idx: u64,
+        duplicate_of_idx: u64,
+        span: Option<SourceSpan>,
+    },
+    /// `subsumed_idx` can never contribute anything `subsuming_idx` doesn't already: the two
+    /// rules' bodies are identical (up to renaming), and `subsuming_idx`'s parameter specializers
+    /// are at least as general, so anything that satisfies `subsumed_idx`'s specializers also
+    /// satisfies `subsuming_idx`'s.
+    SubsumedRule {
+        name: Symbol,
+        subsumed_idx: u64,
+        subsuming_idx: u64,
+        span: Option<SourceSpan>,
+    },
+}
+
+/// Structural equality of two terms up to a consistent renaming of variables: a variable on
+/// either side may stand for anything, as long as it stands for the *same* thing everywhere it
+/// recurs in this comparison. This is what lets `f(x) if foo(x);` and `f(y) if foo(y);` be
+/// recognized as the same rule despite the different parameter names.
+fn alpha_equivalent(a: &Term, b: &Term, renaming: &mut HashMap<Symbol, Symbol>) -> bool {
+    match (a.value(), b.value()) {
+        (Value::Variable(x), Value::Variable(y)) => match renaming.get(x) {
+            Some(mapped) => mapped == y,
+            None => {
+                renaming.insert(x.clone(), y.clone());
+                true
+            }
+        },
+        (
+            Value::Expression(Operation {
+                operator: op_a,
+                args: args_a,
+            }),
+            Value::Expression(Operation {
+                operator: op_b,
+                args: args_b,
+            }),
+        ) => {
+            op_a == op_b
+                && args_a.len() == args_b.len()
+                && args_a
+                    .iter()
+                    .zip(args_b.iter())
+                    .all(|(x, y)| alpha_equivalent(x, y, renaming))
+        }
+        (
+            Value::Call(Call {
+                name: name_a,
+                args: args_a,
+                ..
+            }),
+            Value::Call(Call {
+                name: name_b,
+                args: args_b,
+                ..
+            }),
+        ) => {
+            name_a == name_b
+                && args_a.len() == args_b.len()
+                && args_a
+                    .iter()
+                    .zip(args_b.iter())
+                    .all(|(x, y)| alpha_equivalent(x, y, renaming))
+        }
+        (Value::List(xs), Value::List(ys)) => {
+            xs.len() == ys.len()
+                && xs
+                    .iter()
+                    .zip(ys.iter())
+                    .all(|(x, y)| alpha_equivalent(x, y, renaming))
+        }
+        (
+            Value::Dictionary(Dictionary { fields: xs }),
+            Value::Dictionary(Dictionary { fields: ys }),
+        ) => {
+            xs.len() == ys.len()
+                && xs.iter().all(|(k, x)| {
+                    ys.get(k)
+                        .map_or(false, |y| alpha_equivalent(x, y, renaming))
+                })
+        }
+        (
+            Value::Pattern(Pattern::Instance(InstanceLiteral {
+                tag: tag_a,
+                fields: fields_a,
+            })),
+            Value::Pattern(Pattern::Instance(InstanceLiteral {
+                tag: tag_b,
+                fields: fields_b,
+            })),
+        ) => {
+            tag_a == tag_b
+                && fields_a.fields.len() == fields_b.fields.len()
+                && fields_a.fields.iter().all(|(k, x)| {
+                    fields_b
+                        .fields
+                        .get(k)
+                        .map_or(false, |y| alpha_equivalent(x, y, renaming))
+                })
+        }
+        _ => a.value() == b.value(),
+    }
+}
+
+fn params_alpha_equivalent(
+    a: &[Parameter],
+    b: &[Parameter],
+    renaming: &mut HashMap<Symbol, Symbol>,
+) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b.iter()).all(|(pa, pb)| {
+            alpha_equivalent(&pa.parameter, &pb.parameter, renaming)
+                && match (&pa.specializer, &pb.specializer) {
+                    (Some(sa), Some(sb)) => alpha_equivalent(sa, sb, renaming),
+                    (None, None) => true,
+                    _ => false,
+                }
+        })
+}
+
+/// Does `general`'s parameter list accept at least everything `specific`'s does? Reuses the same
+/// specificity comparison `validate_rules` applies between a loaded rule and its prototype
+/// (`KnowledgeBase::rule_params_match`), treating `general` as the prototype and `specific` as the
+/// rule being checked against it, so this gets dictionary-superset matching, MRO-aware instance
+/// subclassing, and "an unconstrained parameter accepts any specializer" for free instead of
+/// re-deriving a narrower comparison from scratch.
+fn is_at_least_as_general(kb: &KnowledgeBase, general: &Rule, specific: &Rule) -> bool {
+    matches!(
+        kb.rule_params_match(specific, general),
+        Ok(RuleParamMatch::True)
+    )
+}
+
+/// Lint every loaded rule, pairwise within each rule name, for duplicate and subsumed rules.
+/// Returns one diagnostic per offending pair; it's up to the caller (e.g. a CLI or editor
+/// integration) to decide how to surface these -- as warnings, errors, or simply a report.
+pub fn lint_rules(kb: &KnowledgeBase) -> Vec<LintDiagnostic> {
+    let mut diagnostics = vec![];
+    for name in kb.get_rules().keys() {
+        let generic_rule = match kb.get_generic_rule(name) {
+            Some(generic_rule) => generic_rule,
+            None => continue,
+        };
+        let rules: Vec<(u64, _)> = generic_rule
+            .rules
+            .iter()
+            .map(|(idx, rule)| (*idx, rule))
+            .collect();
+        for i in 0..rules.len() {
+            for j in (i + 1)..rules.len() {
+                let (idx_a, rule_a) = rules[i];
+                let (idx_b, rule_b) = rules[j];
+
+                let mut renaming = HashMap::new();
+                let params_equal =
+                    params_alpha_equivalent(&rule_a.params, &rule_b.params, &mut renaming);
+                if params_equal && alpha_equivalent(&rule_a.body, &rule_b.body, &mut renaming) {
+                    diagnostics.push(LintDiagnostic::DuplicateRule {
+                        name: name.clone(),
+                        rule_idx: idx_b,
+                        duplicate_of_idx: idx_a,
+                        span: source_span(kb, &rule_b.body),
+                    });
+                    continue;
+                }
+
+                let mut body_renaming = HashMap::new();
+                if !alpha_equivalent(&rule_a.body, &rule_b.body, &mut body_renaming) {
+                    continue;
+                }
+                if is_at_least_as_general(kb, rule_a, rule_b) {
+                    diagnostics.push(LintDiagnostic::SubsumedRule {
+                        name: name.clone(),
+                        subsumed_idx: idx_b,
+                        subsuming_idx: idx_a,
+                        span: source_span(kb, &rule_b.body),
+                    });
+                } else if is_at_least_as_general(kb, rule_b, rule_a) {
+                    diagnostics.push(LintDiagnostic::SubsumedRule {
+                        name: name.clone(),
+                        subsumed_idx: idx_a,
+                        subsuming_idx: idx_b,
+                        span: source_span(kb, &rule_a.body),
+                    });
+                }
+            }
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint_rules_detects_duplicate() {
+        let mut kb = KnowledgeBase::new();
+        kb.add_rule(rule!("f", ["x"; value!(1)]));
+        kb.add_rule(rule!("f", ["y"; value!(1)]));
+
+        let diagnostics = lint_rules(&kb);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(matches!(
+            &diagnostics[0],
+            LintDiagnostic::DuplicateRule { name, .. } if *name == sym!("f")
+        ));
+    }
+
+    #[test]
+    fn test_lint_rules_detects_subsumption() {
+        let mut kb = KnowledgeBase::new();
+        kb.constant(
+            sym!("Fruit"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 1,
+                constructor: None,
+                repr: None
+            })),
+        );
+        kb.constant(
+            sym!("Citrus"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 2,
+                constructor: None,
+                repr: None
+            })),
+        );
+        kb.add_mro(sym!("Fruit"), vec![1]).unwrap();
+        kb.add_mro(sym!("Citrus"), vec![2, 1]).unwrap();
+
+        kb.add_rule(rule!("f", ["x"; instance!(sym!("Fruit"))]));
+        kb.add_rule(rule!("f", ["x"; instance!(sym!("Citrus"))]));
+
+        let diagnostics = lint_rules(&kb);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(matches!(
+            &diagnostics[0],
+            LintDiagnostic::SubsumedRule { name, .. } if *name == sym!("f")
+        ));
+    }
+
+    #[test]
+    fn test_lint_rules_no_false_positive_for_distinct_rules() {
+        let mut kb = KnowledgeBase::new();
+        kb.add_rule(rule!("f", ["x"; value!(1)]));
+        kb.add_rule(rule!("f", ["x"; value!(2)]));
+
+        assert!(lint_rules(&kb).is_empty());
+    }
+}
@@ -0,0 +1,603 @@
+//! Structural search-and-replace over loaded rules.
+//!
+//! A search/replace "template" is ordinary Polar rule syntax whose variables act as
+//! meta-variables: any variable in a template unifies with whatever term appears in that
+//! position in a candidate rule (consistently across the whole template), while everything
+//! else -- rule name, arity, literal values -- must match exactly. This lets policy tooling find
+//! and rewrite rules (e.g. `allow($actor, $action, $resource) if
+//! role_has_permission($actor, $action);`) without string munging.
+//!
+//! The one exception to "match exactly" is instance specializer tags: a template specializer
+//! like `$resource: Resource` matches not only rule parameters specialized on `Resource` itself
+//! but also on any of `Resource`'s registered subclasses, per the `KnowledgeBase`'s MRO. This
+//! makes a search template usable as a type-constrained placeholder across a whole class
+//! hierarchy rather than one exact type at a time.
+//!
+//! A template list may end in an "any tail" placeholder -- `[$first, *$rest]` -- which matches
+//! zero or more trailing elements and binds `$rest` to whatever's left over, rather than requiring
+//! the candidate list to be exactly as long as the template.
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use super::bindings::Bindings;
+use super::error::{self, PolarResult};
+use super::kb::KnowledgeBase;
+use super::parser::{parse_lines, Line};
+use super::rules::{Parameter, Rule};
+use super::terms::*;
+
+/// A location where a `search_rules` template matched: the rule's name, its index within that
+/// name's `GenericRule`, the meta-variable bindings the match produced, and (when the matched
+/// rule came from a file rather than e.g. `rule!`-constructed test fixtures) the span of source
+/// text it spans, so a caller can point a user at file/line info without re-threading the
+/// `KnowledgeBase` through afterwards.
+#[derive(Debug, Clone)]
+pub struct RuleMatch {
+    pub name: Symbol,
+    pub rule_idx: u64,
+    pub bindings: Bindings,
+    pub span: Option<SourceSpan>,
+}
+
+/// File/line info for a `RuleMatch`, resolved via `term.get_source_id()` + `KnowledgeBase::sources`
+/// at match time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceSpan {
+    pub filename: Option<String>,
+    pub range: Range<usize>,
+}
+
+/// Resolve `term`'s source span against `kb`, if it has one -- synthetic rules built directly via
+/// `rule!` (rather than parsed from a file or string) carry no `SourceInfo` and resolve to `None`.
+/// Shared with `lint.rs`, which points its own diagnostics at the same kind of span.
+pub(crate) fn source_span(kb: &KnowledgeBase, term: &Term) -> Option<SourceSpan> {
+    let source_id = term.get_source_id()?;
+    let source = kb.sources.get_source(source_id)?;
+    let (start, end) = term.span()?;
+    Some(SourceSpan {
+        filename: source.filename,
+        range: start..end,
+    })
+}
+
+/// Drop any match whose source span is strictly contained within another match's span, keeping
+/// only the outermost of a set of overlapping matches so a caller applying edits from the result
+/// never clobbers one rewrite with another nested inside it. Matches with no resolvable span (see
+/// `source_span`) are never filtered out, since there's nothing to compare them against.
+fn retain_outermost(matches: Vec<RuleMatch>) -> Vec<RuleMatch> {
+    let is_nested_in = |inner: &SourceSpan, outer: &SourceSpan| {
+        inner.filename == outer.filename
+            && outer.range.start <= inner.range.start
+            && inner.range.end <= outer.range.end
+            && inner.range != outer.range
+    };
+    let keep: Vec<bool> = matches
+        .iter()
+        .enumerate()
+        .map(|(i, candidate)| match &candidate.span {
+            None => true,
+            Some(candidate_span) => !matches.iter().enumerate().any(|(j, other)| {
+                i != j
+                    && other
+                        .span
+                        .as_ref()
+                        .map_or(false, |other_span| is_nested_in(candidate_span, other_span))
+            }),
+        })
+        .collect();
+    matches
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(rule_match, keep)| keep.then(|| rule_match))
+        .collect()
+}
+
+/// Parse a single rule out of template source, for use as a search or replace template.
+fn parse_template(template: &str) -> PolarResult<Rule> {
+    let lines = parse_lines(0, template)?;
+    match lines.into_iter().next() {
+        Some(Line::Rule(rule)) => Ok(rule),
+        _ => Err(error::ParseError::ParseSugar {
+            loc: 0,
+            msg: format!(
+                "Expected a single rule as a search/replace template, got: {}",
+                template
+            ),
+            ranges: vec![],
+        }
+        .into()),
+    }
+}
+
+/// Does `rule` match `template`? Template variables unify with whatever they're compared
+/// against; everything else must match exactly. Bindings accumulate across the whole rule (head
+/// params and body), so a repeated meta-variable must bind to the same term everywhere it's used.
+fn rule_matches_template(kb: &KnowledgeBase, template: &Rule, rule: &Rule, bindings: &mut Bindings) -> bool {
+    if template.params.len() != rule.params.len() {
+        return false;
+    }
+    let params_match = template
+        .params
+        .iter()
+        .zip(rule.params.iter())
+        .all(|(t, r)| unify_param(kb, t, r, bindings));
+    params_match && unify_term(kb, &template.body, &rule.body, bindings)
+}
+
+fn unify_param(kb: &KnowledgeBase, template: &Parameter, rule: &Parameter, bindings: &mut Bindings) -> bool {
+    if !unify_term(kb, &template.parameter, &rule.parameter, bindings) {
+        return false;
+    }
+    match (&template.specializer, &rule.specializer) {
+        (Some(t), Some(r)) => unify_term(kb, t, r, bindings),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// General structural unification over arbitrary Polar terms (not just specializers): a
+/// meta-variable unifies with anything, binding consistently; everything else recurses
+/// structurally and falls back to equality on leaves.
+///
+/// Type-constrained placeholders -- a parameter like `$actor: Actor` -- are a `Variable`
+/// specializer paired with an instance pattern, so they fall into the `Pattern::Instance` case
+/// below, which accepts not just an exact tag match but also any registered subclass of the
+/// template's tag (per `KnowledgeBase`'s MRO), mirroring how rule prototypes themselves validate
+/// against subclasses in `check_pattern_param`.
+fn unify_term(kb: &KnowledgeBase, template: &Term, target: &Term, bindings: &mut Bindings) -> bool {
+    match template.value() {
+        Value::Variable(_) => unify_meta_var(template, target, bindings),
+        Value::Expression(Operation {
+            operator: t_op,
+            args: t_args,
+        }) => match target.value() {
+            Value::Expression(Operation {
+                operator: r_op,
+                args: r_args,
+            }) if t_op == r_op && t_args.len() == r_args.len() => t_args
+                .iter()
+                .zip(r_args.iter())
+                .all(|(t, r)| unify_term(kb, t, r, bindings)),
+            _ => false,
+        },
+        Value::Call(Call {
+            name: t_name,
+            args: t_args,
+            ..
+        }) => match target.value() {
+            Value::Call(Call {
+                name: r_name,
+                args: r_args,
+                ..
+            }) if t_name == r_name && t_args.len() == r_args.len() => t_args
+                .iter()
+                .zip(r_args.iter())
+                .all(|(t, r)| unify_term(kb, t, r, bindings)),
+            _ => false,
+        },
+        Value::List(t_list) => match target.value() {
+            Value::List(r_list) => {
+                // An "any tail" placeholder -- `[$first, *$rest]` -- is a trailing
+                // `Operator::Rest` wrapping a single meta-variable; it matches zero or more
+                // elements, binding the variable to whatever's left over after the leading,
+                // position-matched elements are consumed.
+                if let Some(Value::Expression(Operation {
+                    operator: Operator::Rest,
+                    args: rest_args,
+                })) = t_list.last().map(Term::value)
+                {
+                    let prefix = &t_list[..t_list.len() - 1];
+                    rest_args.len() == 1
+                        && r_list.len() >= prefix.len()
+                        && prefix
+                            .iter()
+                            .zip(r_list.iter())
+                            .all(|(t, r)| unify_term(kb, t, r, bindings))
+                        && unify_term(
+                            kb,
+                            &rest_args[0],
+                            &target.clone_with_value(Value::List(r_list[prefix.len()..].to_vec())),
+                            bindings,
+                        )
+                } else if t_list.len() == r_list.len() {
+                    t_list
+                        .iter()
+                        .zip(r_list.iter())
+                        .all(|(t, r)| unify_term(kb, t, r, bindings))
+                } else {
+                    false
+                }
+            }
+            _ => false,
+        },
+        Value::Dictionary(Dictionary { fields: t_fields }) => match target.value() {
+            Value::Dictionary(Dictionary { fields: r_fields }) if t_fields.len() == r_fields.len() => {
+                t_fields.iter().all(|(k, t_value)| {
+                    r_fields
+                        .get(k)
+                        .map_or(false, |r_value| unify_term(kb, t_value, r_value, bindings))
+                })
+            }
+            _ => false,
+        },
+        Value::Pattern(Pattern::Instance(InstanceLiteral {
+            tag: t_tag,
+            fields: t_fields,
+        })) => match target.value() {
+            Value::Pattern(Pattern::Instance(InstanceLiteral {
+                tag: r_tag,
+                fields: r_fields,
+            })) if kb.is_subclass_or_equal(r_tag, t_tag) => {
+                t_fields.fields.iter().all(|(k, t_value)| {
+                    r_fields
+                        .fields
+                        .get(k)
+                        .map_or(false, |r_value| unify_term(kb, t_value, r_value, bindings))
+                })
+            }
+            _ => false,
+        },
+        _ => template.value() == target.value(),
+    }
+}
+
+fn unify_meta_var(var_term: &Term, target: &Term, bindings: &mut Bindings) -> bool {
+    let var = var_term.value().as_symbol().expect("variable").clone();
+    match bindings.get(&var).cloned() {
+        Some(bound) => bound.value() == target.value(),
+        None => {
+            bindings.insert(var, target.clone());
+            true
+        }
+    }
+}
+
+/// Substitute `bindings` (captured from a matching `search_rules`/`replace_rules` template) into
+/// `replacement`, producing the rewritten rule. Variables not present in `bindings` are left as-is
+/// (e.g. fresh variables introduced purely in the replacement).
+fn substitute_rule(replacement: &Rule, bindings: &Bindings) -> Rule {
+    let mut rule = replacement.clone();
+    for param in &mut rule.params {
+        param.parameter = substitute_term(&param.parameter, bindings);
+        param.specializer = param
+            .specializer
+            .as_ref()
+            .map(|s| substitute_term(s, bindings));
+    }
+    rule.body = substitute_term(&rule.body, bindings);
+    rule
+}
+
+fn substitute_term(term: &Term, bindings: &Bindings) -> Term {
+    if let Value::Variable(var) = term.value() {
+        if let Some(bound) = bindings.get(var) {
+            return term.clone_with_value(bound.value().clone());
+        }
+    }
+    let substituted = match term.value().clone() {
+        Value::Expression(Operation { operator, args }) => Value::Expression(Operation {
+            operator,
+            args: args.iter().map(|a| substitute_term(a, bindings)).collect(),
+        }),
+        Value::Call(Call { name, args, kwargs }) => Value::Call(Call {
+            name,
+            args: args.iter().map(|a| substitute_term(a, bindings)).collect(),
+            kwargs,
+        }),
+        Value::List(items) => {
+            Value::List(items.iter().map(|i| substitute_term(i, bindings)).collect())
+        }
+        Value::Dictionary(Dictionary { fields }) => Value::Dictionary(Dictionary {
+            fields: fields
+                .into_iter()
+                .map(|(k, v)| (k, substitute_term(&v, bindings)))
+                .collect(),
+        }),
+        other => other,
+    };
+    term.clone_with_value(substituted)
+}
+
+/// A precompiled search (and optional replacement) template: the engine `KnowledgeBase`'s
+/// `search_rules`/`replace_rules` are built on. Policy tooling that runs the same search
+/// repeatedly -- e.g. a lint rule re-applied after every reload, or a bulk audit across many
+/// distinct rewrites -- should build one of these once rather than re-parsing template source on
+/// every call.
+pub struct SearchReplaceEngine {
+    search: Rule,
+    replace: Option<Rule>,
+}
+
+impl SearchReplaceEngine {
+    /// Compile a search-only engine from `search` template source.
+    pub fn new(search: &str) -> PolarResult<Self> {
+        Ok(Self {
+            search: parse_template(search)?,
+            replace: None,
+        })
+    }
+
+    /// Compile a search-and-replace engine.
+    pub fn with_replacement(search: &str, replace: &str) -> PolarResult<Self> {
+        Ok(Self {
+            search: parse_template(search)?,
+            replace: Some(parse_template(replace)?),
+        })
+    }
+
+    /// Find every rule in `kb` matching this engine's search template.
+    pub fn find_in(&self, kb: &KnowledgeBase) -> Vec<RuleMatch> {
+        let mut matches = vec![];
+        if let Some(generic_rule) = kb.get_generic_rule(&self.search.name) {
+            for (rule_idx, rule) in generic_rule.rules.iter() {
+                let mut bindings = Bindings::new();
+                if rule_matches_template(kb, &self.search, rule, &mut bindings) {
+                    matches.push(RuleMatch {
+                        name: self.search.name.clone(),
+                        rule_idx: *rule_idx,
+                        bindings,
+                        span: source_span(kb, &rule.body),
+                    });
+                }
+            }
+        }
+        retain_outermost(matches)
+    }
+
+    /// Rewrite every rule in `kb` matching this engine's search template in place, substituting
+    /// captured bindings into the replacement template and preserving each rewritten rule's
+    /// original `SourceInfo`. Returns the number of rules rewritten. Errors if this engine was
+    /// built with `new` rather than `with_replacement`.
+    pub fn apply_to(&self, kb: &mut KnowledgeBase) -> PolarResult<usize> {
+        let replace = self.replace.as_ref().ok_or_else(|| {
+            error::RuntimeError::TypeError {
+                msg: "SearchReplaceEngine has no replacement template; build it with \
+                      `with_replacement` to call `apply_to`."
+                    .to_owned(),
+                stack_trace: None,
+            }
+            .into()
+        })?;
+        // Matching needs an immutable borrow of `kb` (to consult the MRO for type-constrained
+        // placeholders), which can't overlap with the mutable borrow the rewrite pass below needs
+        // to update `rule`s in place, so collect the matching rules first, then rewrite in a
+        // second pass. Also drop any match nested inside another before rewriting, so applying
+        // the rewrite to an outer match can never invalidate the span an inner match was found at.
+        let matches: Vec<RuleMatch> = match kb.get_generic_rule(&self.search.name) {
+            Some(generic_rule) => generic_rule
+                .rules
+                .iter()
+                .filter_map(|(rule_idx, rule)| {
+                    let mut bindings = Bindings::new();
+                    rule_matches_template(kb, &self.search, rule, &mut bindings).then(|| RuleMatch {
+                        name: self.search.name.clone(),
+                        rule_idx: *rule_idx,
+                        bindings,
+                        span: source_span(kb, &rule.body),
+                    })
+                })
+                .collect(),
+            None => vec![],
+        };
+        let matches = retain_outermost(matches);
+        let mut rewritten = 0;
+        if let Some(generic_rule) = kb.get_generic_rule_mut(&self.search.name) {
+            for rule_match in &matches {
+                if let Some(rule) = generic_rule.rules.get_mut(&rule_match.rule_idx) {
+                    let mut new_rule = substitute_rule(replace, &rule_match.bindings);
+                    new_rule.source_info = rule.source_info.clone();
+                    *rule = Arc::new(new_rule);
+                    rewritten += 1;
+                }
+            }
+        }
+        Ok(rewritten)
+    }
+}
+
+/// Run a batch of search/replace rewrites against `kb` in order, for bulk policy audits that need
+/// to apply many mechanical rewrites in one pass. Returns the total number of rules rewritten
+/// across all of them.
+pub fn apply_rewrites(kb: &mut KnowledgeBase, rewrites: &[(&str, &str)]) -> PolarResult<usize> {
+    let mut total = 0;
+    for (search, replace) in rewrites {
+        total += SearchReplaceEngine::with_replacement(search, replace)?.apply_to(kb)?;
+    }
+    Ok(total)
+}
+
+/// Find every loaded rule whose head and body structurally match `template`.
+pub(crate) fn search_rules(kb: &KnowledgeBase, template: &str) -> PolarResult<Vec<RuleMatch>> {
+    Ok(SearchReplaceEngine::new(template)?.find_in(kb))
+}
+
+/// Replace every rule matching `search` with `replace` (with `replace`'s meta-variables filled in
+/// from the match), preserving each rewritten rule's original `SourceInfo`. Returns the number of
+/// rules rewritten.
+pub(crate) fn replace_rules(
+    kb: &mut KnowledgeBase,
+    search: &str,
+    replace: &str,
+) -> PolarResult<usize> {
+    SearchReplaceEngine::with_replacement(search, replace)?.apply_to(kb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add(kb: &mut KnowledgeBase, src: &str) {
+        kb.add_rule(parse_template(src).unwrap());
+    }
+
+    #[test]
+    fn test_rule_matches_template_binds_meta_variable_consistently() {
+        let kb = KnowledgeBase::new();
+        let template = parse_template("f(x) if foo(x);").unwrap();
+        let matching = parse_template("f(y) if foo(y);").unwrap();
+        let mismatched = parse_template("f(y) if foo(z);").unwrap();
+        let wrong_call = parse_template("f(y) if bar(y);").unwrap();
+
+        let mut bindings = Bindings::new();
+        assert!(rule_matches_template(&kb, &template, &matching, &mut bindings));
+        assert_eq!(bindings.get(&sym!("x")).unwrap().value(), &value!(sym!("y")));
+
+        assert!(!rule_matches_template(
+            &kb,
+            &template,
+            &mismatched,
+            &mut Bindings::new()
+        ));
+        assert!(!rule_matches_template(
+            &kb,
+            &template,
+            &wrong_call,
+            &mut Bindings::new()
+        ));
+    }
+
+    #[test]
+    fn test_unify_term_rest_placeholder_binds_trailing_elements() {
+        let kb = KnowledgeBase::new();
+        let template = term!(Value::List(vec![
+            term!(value!(1)),
+            term!(Value::Expression(Operation {
+                operator: Operator::Rest,
+                args: vec![term!(value!(sym!("rest")))],
+            })),
+        ]));
+        let target = term!(Value::List(vec![
+            term!(value!(1)),
+            term!(value!(2)),
+            term!(value!(3)),
+        ]));
+
+        let mut bindings = Bindings::new();
+        assert!(unify_term(&kb, &template, &target, &mut bindings));
+        assert_eq!(
+            bindings.get(&sym!("rest")).unwrap().value(),
+            &Value::List(vec![term!(value!(2)), term!(value!(3))])
+        );
+
+        // The placeholder matches zero trailing elements too.
+        let empty_tail = term!(Value::List(vec![term!(value!(1))]));
+        let mut bindings = Bindings::new();
+        assert!(unify_term(&kb, &template, &empty_tail, &mut bindings));
+        assert_eq!(
+            bindings.get(&sym!("rest")).unwrap().value(),
+            &Value::List(vec![])
+        );
+    }
+
+    #[test]
+    fn test_unify_term_instance_specializer_matches_registered_subclass() {
+        let mut kb = KnowledgeBase::new();
+        kb.constant(
+            sym!("Resource"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 1,
+                constructor: None,
+                repr: None
+            })),
+        );
+        kb.constant(
+            sym!("Repo"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 2,
+                constructor: None,
+                repr: None
+            })),
+        );
+        kb.add_mro(sym!("Resource"), vec![1]).unwrap();
+        kb.add_mro(sym!("Repo"), vec![2, 1]).unwrap();
+
+        let template = term!(value!(pattern!(instance!(sym!("Resource")))));
+        let subclass_target = term!(value!(pattern!(instance!(sym!("Repo")))));
+        let unrelated_target = term!(value!(pattern!(instance!(sym!("Widget")))));
+
+        assert!(unify_term(
+            &kb,
+            &template,
+            &subclass_target,
+            &mut Bindings::new()
+        ));
+        assert!(!unify_term(
+            &kb,
+            &template,
+            &unrelated_target,
+            &mut Bindings::new()
+        ));
+    }
+
+    fn span(start: usize, end: usize) -> Option<SourceSpan> {
+        Some(SourceSpan {
+            filename: None,
+            range: start..end,
+        })
+    }
+
+    fn dummy_match(idx: u64, span: Option<SourceSpan>) -> RuleMatch {
+        RuleMatch {
+            name: sym!("f"),
+            rule_idx: idx,
+            bindings: Bindings::new(),
+            span,
+        }
+    }
+
+    #[test]
+    fn test_retain_outermost_drops_nested_match() {
+        let matches = vec![
+            dummy_match(0, span(0, 100)),
+            dummy_match(1, span(10, 20)),
+        ];
+        let kept = retain_outermost(matches);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].rule_idx, 0);
+    }
+
+    #[test]
+    fn test_retain_outermost_keeps_non_overlapping_and_unspanned_matches() {
+        let matches = vec![
+            dummy_match(0, span(0, 10)),
+            dummy_match(1, span(20, 30)),
+            dummy_match(2, None),
+        ];
+        let kept = retain_outermost(matches);
+        assert_eq!(kept.len(), 3);
+    }
+
+    #[test]
+    fn test_find_in_returns_structurally_matching_rules() {
+        let mut kb = KnowledgeBase::new();
+        add(&mut kb, "f(y) if foo(y);");
+        add(&mut kb, "f(y) if bar(y);");
+
+        let engine = SearchReplaceEngine::new("f(x) if foo(x);").unwrap();
+        let matches = engine.find_in(&kb);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].bindings.get(&sym!("x")).unwrap().value(), &value!(sym!("y")));
+    }
+
+    #[test]
+    fn test_apply_to_rewrites_matching_rule_and_leaves_others() {
+        let mut kb = KnowledgeBase::new();
+        add(&mut kb, "f(y) if foo(y);");
+        add(&mut kb, "f(y) if bar(y);");
+
+        let rewritten = replace_rules(&mut kb, "f(x) if foo(x);", "f(x) if baz(x);").unwrap();
+        assert_eq!(rewritten, 1);
+
+        let bodies: Vec<String> = kb
+            .get_generic_rule(&sym!("f"))
+            .unwrap()
+            .rules
+            .values()
+            .map(|rule| rule.body.to_polar())
+            .collect();
+        assert!(bodies.iter().any(|b| b.contains("baz")));
+        assert!(bodies.iter().any(|b| b.contains("bar")));
+        assert!(!bodies.iter().any(|b| b.contains("foo")));
+    }
+}
@@ -1,6 +1,6 @@
 #![allow(dead_code)]
 
-use super::error::{PolarResult, RolesValidationError};
+use super::error::{PolarError, PolarResult, RolesValidationError};
 use super::events::ResultEvent;
 use super::rules::GenericRule;
 use super::terms::*;
@@ -19,7 +19,7 @@ struct Role {
     implied_roles: Vec<String>,
 }
 
-struct Resource {
+pub(crate) struct Resource {
     typ: String,
     name: String,
     actions: Vec<String>,
@@ -60,259 +60,642 @@ pub fn validate_roles_config(
     roles_config: Vec<Vec<ResultEvent>>,
 ) -> PolarResult<()> {
     validate_actor_has_role_for_resource(rules)?;
+    match validate_roles_config_all(roles_config) {
+        Ok(()) => Ok(()),
+        Err(errors) => Err(combine_roles_errors(errors)),
+    }
+}
+
+/// Like `validate_roles_config`, but surfaces every problem found as its own
+/// `RolesValidationError`-backed `PolarError` instead of flattening them into one combined
+/// message -- so a host that wants to e.g. report each issue against its own source location, or
+/// render them as a list in a UI, doesn't have to re-parse `combine_roles_errors`'s numbered-list
+/// string to get them back apart.
+pub fn validate_roles_config_all(
+    roles_config: Vec<Vec<ResultEvent>>,
+) -> Result<(), Vec<PolarError>> {
     let role_resources = roles_config.first().ok_or_else(|| {
         // TODO: add link to docs in error message
-        RolesValidationError(
+        vec![RolesValidationError(
             "Need to define at least one `resource(type, name, actions, roles)` predicate to use Oso Roles.".to_owned(),
         )
+        .into()]
     })?;
     if role_resources.is_empty() {
-        return Err(RolesValidationError(
+        return Err(vec![RolesValidationError(
             "Need to define at least one `resource(type, name, actions, roles)` predicate to use Oso Roles.".to_owned(),
         )
-        .into());
+        .into()]);
     }
 
     let mut resources = HashMap::new();
+    let mut errors = vec![];
     for result in role_resources {
-        let resource_def = result
-            .bindings
-            .get(&Symbol::new("resource"))
-            .unwrap()
-            .value();
-        let resource_name = result.bindings.get(&Symbol::new("name")).unwrap().value();
-        let resource_actions = result
-            .bindings
-            .get(&Symbol::new("actions"))
-            .unwrap()
-            .value();
-        let resource_roles = result.bindings.get(&Symbol::new("roles")).unwrap().value();
-
-        let typ = {
-            if let Value::Expression(Operation {
-                operator: Operator::And,
-                args: and_args,
-            }) = resource_def
-            {
-                match &and_args[..] {
-                    [arg] => {
-                        if let Value::Expression(Operation {
-                            operator: Operator::Isa,
-                            args: isa_args,
-                        }) = arg.value()
-                        {
-                            match &isa_args[..] {
-                                [this_expr, typ_expr] => {
-                                    if let Value::Variable(Symbol(sym)) = this_expr.value() {
-                                        if sym != "_this" {
-                                            return Err(RolesValidationError(
-                                                "Invalid resource, no type specializer.".to_owned(),
-                                            )
-                                            .into());
-                                        }
-                                    } else {
-                                        return Err(RolesValidationError(
-                                            "Invalid resource, no type specializer.".to_owned(),
-                                        )
-                                        .into());
-                                    }
-                                    if let Value::Pattern(Pattern::Instance(InstanceLiteral {
-                                        tag,
-                                        ..
-                                    })) = typ_expr.value()
-                                    {
-                                        tag.0.clone()
-                                    } else {
+        match parse_resource_definition(result) {
+            Ok(resource) => {
+                if resources.contains_key(&resource.name) {
+                    errors.push(
+                        RolesValidationError(format!(
+                            "Duplicate resource name {}.",
+                            resource.name
+                        ))
+                        .into(),
+                    );
+                } else {
+                    resources.insert(resource.name.clone(), resource);
+                }
+            }
+            Err(error) => errors.push(error),
+        }
+    }
+
+    errors.extend(validate_role_implications_and_permissions(&resources));
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    Ok(())
+}
+
+/// Parse a single `resource(type, name, actions, roles)` result into a `Resource`. Pulled out of
+/// `validate_roles_config` so each resource definition's parse errors can be collected
+/// independently instead of aborting the whole config at the first malformed entry.
+fn parse_resource_definition(result: &ResultEvent) -> PolarResult<Resource> {
+let resource_def = result
+    .bindings
+    .get(&Symbol::new("resource"))
+    .unwrap()
+    .value();
+let resource_name = result.bindings.get(&Symbol::new("name")).unwrap().value();
+let resource_actions = result
+    .bindings
+    .get(&Symbol::new("actions"))
+    .unwrap()
+    .value();
+let resource_roles = result.bindings.get(&Symbol::new("roles")).unwrap().value();
+
+    let typ = {
+        if let Value::Expression(Operation {
+            operator: Operator::And,
+            args: and_args,
+        }) = resource_def
+        {
+            match &and_args[..] {
+                [arg] => {
+                    if let Value::Expression(Operation {
+                        operator: Operator::Isa,
+                        args: isa_args,
+                    }) = arg.value()
+                    {
+                        match &isa_args[..] {
+                            [this_expr, typ_expr] => {
+                                if let Value::Variable(Symbol(sym)) = this_expr.value() {
+                                    if sym != "_this" {
                                         return Err(RolesValidationError(
                                             "Invalid resource, no type specializer.".to_owned(),
                                         )
                                         .into());
                                     }
+                                } else {
+                                    return Err(RolesValidationError(
+                                        "Invalid resource, no type specializer.".to_owned(),
+                                    )
+                                    .into());
                                 }
-                                _ => {
+                                if let Value::Pattern(Pattern::Instance(InstanceLiteral {
+                                    tag,
+                                    ..
+                                })) = typ_expr.value()
+                                {
+                                    tag.0.clone()
+                                } else {
                                     return Err(RolesValidationError(
                                         "Invalid resource, no type specializer.".to_owned(),
                                     )
                                     .into());
                                 }
                             }
-                        } else {
-                            return Err(RolesValidationError(
-                                "Invalid resource, no type specializer.".to_owned(),
-                            )
-                            .into());
+                            _ => {
+                                return Err(RolesValidationError(
+                                    "Invalid resource, no type specializer.".to_owned(),
+                                )
+                                .into());
+                            }
                         }
-                    }
-                    _ => {
+                    } else {
                         return Err(RolesValidationError(
                             "Invalid resource, no type specializer.".to_owned(),
                         )
                         .into());
                     }
                 }
-            } else {
-                return Err(RolesValidationError(
-                    "Invalid resource, no type specializer.".to_owned(),
-                )
-                .into());
+                _ => {
+                    return Err(RolesValidationError(
+                        "Invalid resource, no type specializer.".to_owned(),
+                    )
+                    .into());
+                }
             }
-        };
+        } else {
+            return Err(RolesValidationError(
+                "Invalid resource, no type specializer.".to_owned(),
+            )
+            .into());
+        }
+    };
 
-        let name = {
-            if let Value::String(name) = resource_name {
-                name.clone()
-            } else {
-                return Err(RolesValidationError(
-                    "Invalid resource, name is not a string.".to_owned(),
-                )
-                .into());
-            }
-        };
-
-        let actions: Vec<String> = {
-            let mut action_strings = vec![];
-            match resource_actions {
-                Value::List(actions) => {
-                    for a in actions {
-                        if let Value::String(action) = a.value() {
-                            action_strings.push(action.clone());
-                        } else {
-                            return Err(RolesValidationError(
-                                "Invalid action, not a string.".to_owned(),
-                            )
-                            .into());
-                        }
+    let name = {
+        if let Value::String(name) = resource_name {
+            name.clone()
+        } else {
+            return Err(RolesValidationError(
+                "Invalid resource, name is not a string.".to_owned(),
+            )
+            .into());
+        }
+    };
+
+    let actions: Vec<String> = {
+        let mut action_strings = vec![];
+        match resource_actions {
+            Value::List(actions) => {
+                for a in actions {
+                    if let Value::String(action) = a.value() {
+                        action_strings.push(action.clone());
+                    } else {
+                        return Err(RolesValidationError(
+                            "Invalid action, not a string.".to_owned(),
+                        )
+                        .into());
                     }
                 }
-                Value::Variable(_) => (),
-                _ => return Err(RolesValidationError("Invalid actions.".to_owned()).into()),
-            }
-            action_strings
-        };
-
-        let mut acts = HashSet::new();
-        for action in &actions {
-            if acts.contains(action) {
-                return Err(RolesValidationError(format!(
-                    "Duplicate action {} for {}.",
-                    action, typ
-                ))
-                .into());
             }
-            acts.insert(action.to_owned());
+            Value::Variable(_) => (),
+            _ => return Err(RolesValidationError("Invalid actions.".to_owned()).into()),
         }
+        action_strings
+    };
 
-        let mut role_definitions = HashMap::new();
-        if let Value::Dictionary(Dictionary { fields: dict }) = resource_roles {
-            for (name_sym, definition) in dict.iter() {
-                let role_name = name_sym.0.clone();
-                if let Value::Dictionary(Dictionary { fields: def_dict }) = definition.value() {
-                    for key in def_dict.keys() {
-                        if key.0 != "permissions" && key.0 != "implies" {
-                            return Err(RolesValidationError(format!(
-                                "Role definition contains invalid key: {}",
-                                key.0
-                            ))
-                            .into());
-                        }
+    let mut acts = HashSet::new();
+    for action in &actions {
+        if acts.contains(action) {
+            return Err(RolesValidationError(format!(
+                "Duplicate action {} for {}.",
+                action, typ
+            ))
+            .into());
+        }
+        acts.insert(action.to_owned());
+    }
+
+    let mut role_definitions = HashMap::new();
+    if let Value::Dictionary(Dictionary { fields: dict }) = resource_roles {
+        for (name_sym, definition) in dict.iter() {
+            let role_name = name_sym.0.clone();
+            if let Value::Dictionary(Dictionary { fields: def_dict }) = definition.value() {
+                for key in def_dict.keys() {
+                    if key.0 != "permissions" && key.0 != "implies" {
+                        return Err(RolesValidationError(format!(
+                            "Role definition contains invalid key: {}",
+                            key.0
+                        ))
+                        .into());
                     }
-                    let actions = {
-                        let actions_value = def_dict.get(&Symbol::new("permissions"));
-                        if let Some(actions_term) = actions_value {
-                            if let Value::List(actions_list) = actions_term.value() {
-                                let mut actions = vec![];
-                                for action_term in actions_list {
-                                    if let Value::String(action) = action_term.value() {
-                                        actions.push(action.clone())
-                                    } else {
-                                        return Err(RolesValidationError(format!(
-                                            "Invalid actions for role {}, must be a string.",
-                                            role_name
-                                        ))
-                                        .into());
-                                    }
+                }
+                let actions = {
+                    let actions_value = def_dict.get(&Symbol::new("permissions"));
+                    if let Some(actions_term) = actions_value {
+                        if let Value::List(actions_list) = actions_term.value() {
+                            let mut actions = vec![];
+                            for action_term in actions_list {
+                                if let Value::String(action) = action_term.value() {
+                                    actions.push(action.clone())
+                                } else {
+                                    return Err(RolesValidationError(format!(
+                                        "Invalid actions for role {}, must be a string.",
+                                        role_name
+                                    ))
+                                    .into());
                                 }
-                                actions
-                            } else {
-                                return Err(RolesValidationError(format!(
-                                    "Invalid actions for role {}",
-                                    role_name
-                                ))
-                                .into());
                             }
+                            actions
                         } else {
-                            vec![]
+                            return Err(RolesValidationError(format!(
+                                "Invalid actions for role {}",
+                                role_name
+                            ))
+                            .into());
                         }
-                    };
-                    let implications = {
-                        let implications_value = def_dict.get(&Symbol::new("implies"));
-                        if let Some(implications_term) = implications_value {
-                            if let Value::List(implications_list) = implications_term.value() {
-                                let mut implications = vec![];
-                                for implies_term in implications_list {
-                                    if let Value::String(implies) = implies_term.value() {
-                                        implications.push(implies.clone())
-                                    } else {
-                                        return Err(RolesValidationError(format!(
-                                            "Invalid implies for role {}, must be a string.",
-                                            role_name
-                                        ))
-                                        .into());
-                                    }
+                    } else {
+                        vec![]
+                    }
+                };
+                let implications = {
+                    let implications_value = def_dict.get(&Symbol::new("implies"));
+                    if let Some(implications_term) = implications_value {
+                        if let Value::List(implications_list) = implications_term.value() {
+                            let mut implications = vec![];
+                            for implies_term in implications_list {
+                                if let Value::String(implies) = implies_term.value() {
+                                    implications.push(implies.clone())
+                                } else {
+                                    return Err(RolesValidationError(format!(
+                                        "Invalid implies for role {}, must be a string.",
+                                        role_name
+                                    ))
+                                    .into());
                                 }
-                                implications
-                            } else {
-                                return Err(RolesValidationError(format!(
-                                    "Invalid implies for role {}",
-                                    role_name
-                                ))
-                                .into());
                             }
+                            implications
                         } else {
-                            vec![]
+                            return Err(RolesValidationError(format!(
+                                "Invalid implies for role {}",
+                                role_name
+                            ))
+                            .into());
                         }
-                    };
-                    if actions.is_empty() && implications.is_empty() {
-                        return Err(RolesValidationError(
-                            "Must define actions or implications for a role.".to_owned(),
-                        )
-                        .into());
-                    }
-                    let role = Role {
-                        name: role_name.clone(),
-                        typ: typ.clone(),
-                        actions,
-                        implied_roles: implications,
-                    };
-                    if role_definitions.contains_key(&role_name) {
-                        return Err(RolesValidationError(format!(
-                            "Duplicate role name {}.",
-                            role_name
-                        ))
-                        .into());
+                    } else {
+                        vec![]
                     }
-                    role_definitions.insert(role_name, role)
-                } else {
-                    return Err(RolesValidationError("Invalid role definitions".to_owned()).into());
                 };
+                if actions.is_empty() && implications.is_empty() {
+                    return Err(RolesValidationError(
+                        "Must define actions or implications for a role.".to_owned(),
+                    )
+                    .into());
+                }
+                let role = Role {
+                    name: role_name.clone(),
+                    typ: typ.clone(),
+                    actions,
+                    implied_roles: implications,
+                };
+                if role_definitions.contains_key(&role_name) {
+                    return Err(RolesValidationError(format!(
+                        "Duplicate role name {}.",
+                        role_name
+                    ))
+                    .into());
+                }
+                role_definitions.insert(role_name, role)
+            } else {
+                return Err(RolesValidationError("Invalid role definitions".to_owned()).into());
+            };
+        }
+    }
+
+    if actions.is_empty() && role_definitions.is_empty() {
+        return Err(RolesValidationError("Must define actions or roles.".to_owned()).into());
+    }
+
+    Ok(Resource {
+        typ,
+        name,
+        actions,
+        roles: role_definitions,
+    })
+}
+
+/// Combine multiple roles-config errors into one that reports all of them, instead of just
+/// whichever happened to be collected first. A lone error is passed through unchanged.
+fn combine_roles_errors(mut errors: Vec<PolarError>) -> PolarError {
+    if errors.len() == 1 {
+        return errors.pop().unwrap();
+    }
+    let msg = errors
+        .iter()
+        .enumerate()
+        .map(|(i, error)| format!("{}. {}", i + 1, error))
+        .collect::<Vec<_>>()
+        .join("\n");
+    RolesValidationError(msg).into()
+}
+
+/// For every resource, check that each role's permissions and implied roles actually refer to
+/// something declared on that resource, and that the implication graph among its roles has no
+/// cycles (a role that (transitively) implies itself would make permission resolution loop
+/// forever). Collects every problem found rather than stopping at the first.
+fn validate_role_implications_and_permissions(
+    resources: &HashMap<String, Resource>,
+) -> Vec<PolarError> {
+    let mut errors = vec![];
+    for resource in resources.values() {
+        for role in resource.roles.values() {
+            for permission in &role.actions {
+                if !resource.actions.contains(permission) {
+                    let suggestion = did_you_mean(permission, resource.actions.iter());
+                    errors.push(
+                        RolesValidationError(format!(
+                            "Role '{}' on resource '{}' grants undeclared permission '{}'.{}",
+                            role.name, resource.name, permission, suggestion
+                        ))
+                        .into(),
+                    );
+                }
+            }
+            for implied in &role.implied_roles {
+                if !resource.roles.contains_key(implied) {
+                    let suggestion = did_you_mean(implied, resource.roles.keys());
+                    errors.push(
+                        RolesValidationError(format!(
+                            "Role '{}' on resource '{}' implies undeclared role '{}'.{}",
+                            role.name, resource.name, implied, suggestion
+                        ))
+                        .into(),
+                    );
+                }
+            }
+        }
+        errors.extend(check_for_implication_cycles(resource));
+    }
+    errors
+}
+
+/// How many single-character insertions, deletions, or substitutions it takes to turn `a` into
+/// `b`. Used by `did_you_mean` to find the closest declared name to an undeclared one.
+fn levenshtein_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut prev_diag = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let temp = row[j + 1];
+            row[j + 1] = if ca == cb {
+                prev_diag
+            } else {
+                1 + prev_diag.min(row[j]).min(row[j + 1])
+            };
+            prev_diag = temp;
+        }
+    }
+    row[b.len()]
+}
+
+/// If some `candidate` is close enough to `name` to plausibly be a typo of it, render a
+/// " Did you mean 'candidate'?" suffix to append to an "undeclared X" error message; otherwise an
+/// empty string, so the caller can always splice the result onto its message unconditionally. The
+/// distance threshold is a third of the name's length but never less than 2, so e.g. a
+/// 3-character typo isn't suggested for a 4-character name, but is for a 20-character one.
+fn did_you_mean<'a>(name: &str, candidates: impl IntoIterator<Item = &'a String>) -> String {
+    let threshold = (name.chars().count() / 3).max(2);
+    candidates
+        .into_iter()
+        .map(|candidate| (candidate, levenshtein_distance(name, candidate)))
+        .filter(|(_, distance)| *distance <= threshold)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(candidate, _)| format!(" Did you mean '{}'?", candidate))
+        .unwrap_or_default()
+}
+
+/// Depth-first search over a resource's role-implication graph, reporting one error per distinct
+/// cycle found (a role already on the current DFS stack reached again). `path` tracks the roles
+/// currently on the stack so each cycle can be reported as a readable chain, e.g.
+/// `"admin" -> "writer" -> "admin"`.
+fn check_for_implication_cycles(resource: &Resource) -> Vec<PolarError> {
+    let mut errors = vec![];
+    let mut visited = HashSet::new();
+    for role_name in resource.roles.keys() {
+        if !visited.contains(role_name) {
+            let mut path = vec![];
+            if let Err(error) = visit_role(resource, role_name, &mut path, &mut visited) {
+                errors.push(error);
             }
         }
+    }
+    errors
+}
 
-        if actions.is_empty() && role_definitions.is_empty() {
-            return Err(RolesValidationError("Must define actions or roles.".to_owned()).into());
+/// Compute, for every role on every resource, the full set of actions it ultimately grants --
+/// its own `actions` plus the `actions` of every role reachable (transitively, deduplicated)
+/// through `implied_roles`, analogous to role-hierarchy resolution in RBAC systems like casbin's
+/// role manager. Keyed by `(resource name, role name)` so a host can cache authorization
+/// decisions without re-walking the implication graph on every check.
+///
+/// Terminates on a config with an implication cycle too: `collect_implied_permissions` expands
+/// each role at most once per lookup, so every role on a cycle gets the union of the actions
+/// reachable around it. `validate_roles_config` rejects such configs separately, via
+/// `validate_role_implications_and_permissions`.
+pub(crate) fn effective_permissions(
+    resources: &HashMap<String, Resource>,
+) -> HashMap<(String, String), HashSet<String>> {
+    let mut effective = HashMap::new();
+    for resource in resources.values() {
+        for role_name in resource.roles.keys() {
+            let mut visited = HashSet::new();
+            let permissions = collect_implied_permissions(resource, role_name, &mut visited);
+            effective.insert((resource.name.clone(), role_name.clone()), permissions);
         }
+    }
+    effective
+}
 
-        let resource = Resource {
-            typ: typ.clone(),
-            name: name.clone(),
-            actions,
-            roles: role_definitions,
-        };
-        if resources.contains_key(&name) {
-            return Err(RolesValidationError(format!("Duplicate resource name {}.", name)).into());
+/// Union `role_name`'s own actions with those of every role it (transitively) implies, stopping
+/// at roles already in `visited` so a role reachable through more than one path is only expanded
+/// once.
+fn collect_implied_permissions(
+    resource: &Resource,
+    role_name: &str,
+    visited: &mut HashSet<String>,
+) -> HashSet<String> {
+    let mut permissions = HashSet::new();
+    if !visited.insert(role_name.to_owned()) {
+        return permissions;
+    }
+    if let Some(role) = resource.roles.get(role_name) {
+        permissions.extend(role.actions.iter().cloned());
+        for implied in &role.implied_roles {
+            permissions.extend(collect_implied_permissions(resource, implied, visited));
         }
-        resources.insert(name, resource);
     }
+    permissions
+}
 
+fn visit_role<'a>(
+    resource: &'a Resource,
+    role_name: &'a String,
+    path: &mut Vec<&'a String>,
+    visited: &mut HashSet<String>,
+) -> PolarResult<()> {
+    if let Some(cycle_start) = path.iter().position(|name| *name == role_name) {
+        let mut cycle: Vec<&str> = path[cycle_start..].iter().map(|s| s.as_str()).collect();
+        cycle.push(role_name);
+        // Mark every role on `path` -- not just the cycle itself -- as visited before returning.
+        // The outer loop in `check_for_implication_cycles` only restarts a DFS from roles not yet
+        // in `visited`; if an acyclic prefix that merely *leads into* the cycle (e.g. `x -> y` in
+        // `x -> y -> a -> b -> c -> a`) were left unmarked, the outer loop would restart from `x`
+        // and rediscover (and re-report) the very same cycle.
+        for name in path.iter() {
+            visited.insert((*name).clone());
+        }
+        return Err(RolesValidationError(format!(
+            "Circular role implication on resource '{}': {}.",
+            resource.name,
+            cycle.join(" -> ")
+        ))
+        .into());
+    }
+    // Already fully explored (or already part of a reported cycle) from an earlier DFS root.
+    if visited.contains(role_name) {
+        return Ok(());
+    }
+    path.push(role_name);
+    if let Some(role) = resource.roles.get(role_name) {
+        for implied in &role.implied_roles {
+            visit_role(resource, implied, path, visited)?;
+        }
+    }
+    path.pop();
+    visited.insert(role_name.clone());
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_role(name: &str, actions: &[&str], implied_roles: &[&str]) -> Role {
+        Role {
+            name: name.to_owned(),
+            typ: "Org".to_owned(),
+            actions: actions.iter().map(|s| s.to_string()).collect(),
+            implied_roles: implied_roles.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn test_resource(name: &str, actions: &[&str], roles: Vec<Role>) -> Resource {
+        Resource {
+            typ: name.to_owned(),
+            name: name.to_owned(),
+            actions: actions.iter().map(|s| s.to_string()).collect(),
+            roles: roles.into_iter().map(|role| (role.name.clone(), role)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_did_you_mean_suggests_close_match() {
+        let candidates = vec!["read".to_owned(), "write".to_owned()];
+        assert_eq!(
+            did_you_mean("raed", candidates.iter()),
+            " Did you mean 'read'?"
+        );
+        assert_eq!(did_you_mean("completely_different", candidates.iter()), "");
+    }
+
+    #[test]
+    fn test_validate_role_implications_and_permissions_detects_undeclared_references() {
+        let mut resources = HashMap::new();
+        resources.insert(
+            "Org".to_owned(),
+            test_resource(
+                "Org",
+                &["read", "write", "delete"],
+                vec![
+                    test_role("member", &["raed"], &["adminn"]),
+                    test_role("admin", &["delete"], &[]),
+                ],
+            ),
+        );
+
+        let errors = validate_role_implications_and_permissions(&resources);
+        let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        assert_eq!(messages.len(), 2);
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("undeclared permission 'raed'") && m.contains("Did you mean 'read'?")));
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("undeclared role 'adminn'") && m.contains("Did you mean 'admin'?")));
+    }
+
+    #[test]
+    fn test_check_for_implication_cycles_reports_each_cycle_once() {
+        let resource = test_resource(
+            "Org",
+            &["read"],
+            vec![
+                test_role("a", &[], &["b"]),
+                test_role("b", &[], &["c"]),
+                test_role("c", &[], &["a"]),
+            ],
+        );
+
+        let errors = check_for_implication_cycles(&resource);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().contains("Circular role implication"));
+    }
+
+    #[test]
+    fn test_check_for_implication_cycles_reports_once_despite_acyclic_prefix() {
+        // `x -> y` is an acyclic chain feeding into the cycle `a -> b -> c -> a`. The outer loop
+        // in `check_for_implication_cycles` iterates roles in `HashMap` order, so it may start
+        // the DFS from `x`, `y`, or any of `a`/`b`/`c` -- only one cycle report should ever come
+        // out regardless of where it starts.
+        let resource = test_resource(
+            "Org",
+            &["read"],
+            vec![
+                test_role("x", &[], &["y"]),
+                test_role("y", &[], &["a"]),
+                test_role("a", &[], &["b"]),
+                test_role("b", &[], &["c"]),
+                test_role("c", &[], &["a"]),
+            ],
+        );
+
+        let errors = check_for_implication_cycles(&resource);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().contains("Circular role implication"));
+    }
+
+    #[test]
+    fn test_effective_permissions_computes_transitive_closure() {
+        let mut resources = HashMap::new();
+        resources.insert(
+            "Org".to_owned(),
+            test_resource(
+                "Org",
+                &["read", "write", "admin"],
+                vec![
+                    test_role("viewer", &["read"], &[]),
+                    test_role("editor", &["write"], &["viewer"]),
+                    test_role("owner", &["admin"], &["editor"]),
+                ],
+            ),
+        );
+
+        let effective = effective_permissions(&resources);
+        let expected: HashSet<String> = vec!["admin", "write", "read"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            effective[&("Org".to_owned(), "owner".to_owned())],
+            expected
+        );
+    }
+
+    #[test]
+    fn test_validate_roles_config_all_returns_structured_errors() {
+        let errors =
+            validate_roles_config_all(vec![vec![]]).expect_err("empty resource list is invalid");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0]
+            .to_string()
+            .contains("Need to define at least one"));
+    }
+
+    #[test]
+    fn test_combine_roles_errors_joins_multiple_messages() {
+        let errors: Vec<PolarError> = vec![
+            RolesValidationError("first problem".to_owned()).into(),
+            RolesValidationError("second problem".to_owned()).into(),
+        ];
+        let combined = combine_roles_errors(errors);
+        let msg = combined.to_string();
+        assert!(msg.contains("1. first problem"));
+        assert!(msg.contains("2. second problem"));
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::rules::Rule;
+
+/// Bump this whenever the on-disk cache format (or the semantics of what gets cached) changes, so
+/// stale entries from an older `oso` version are treated as misses instead of corrupting a reload.
+const CACHE_FORMAT_VERSION: u64 = 1;
+
+/// Everything `KnowledgeBase` needs to skip re-parsing and re-validating a `Source` it has already
+/// seen: the rules and rule prototypes it parsed out of the source, and the outcome of running
+/// `validate_rules` against just that source (`None` if it passed).
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CachedSource {
+    pub rules: Vec<Rule>,
+    pub rule_prototypes: Vec<Rule>,
+    pub validation_error: Option<String>,
+}
+
+/// A persistent, on-disk cache of parsed-and-validated `Source`s, keyed by a hash of the source
+/// text plus `CACHE_FORMAT_VERSION`. Backed by one file per entry under `root` rather than a full
+/// embedded database, so enabling it doesn't pull in a new storage dependency just to memoize
+/// reloads of large, mostly-unchanged policies.
+pub struct SourceCache {
+    root: PathBuf,
+}
+
+impl SourceCache {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn key_for(src: &str) -> String {
+        let mut hasher = DefaultHasher::new();
+        CACHE_FORMAT_VERSION.hash(&mut hasher);
+        src.hash(&mut hasher);
+        format!("{:016x}.json", hasher.finish())
+    }
+
+    fn path_for(&self, src: &str) -> PathBuf {
+        self.root.join(Self::key_for(src))
+    }
+
+    /// Look up a previously cached parse/validation result for `src`. Returns `None` on a miss
+    /// (including a version mismatch, I/O error, or corrupt entry -- all of which should just fall
+    /// back to a normal parse rather than fail the load).
+    pub(crate) fn get(&self, src: &str) -> Option<CachedSource> {
+        let contents = fs::read(self.path_for(src)).ok()?;
+        serde_json::from_slice(&contents).ok()
+    }
+
+    /// Write the parsed rules, rule prototypes, and validation outcome for `src` to the cache.
+    /// Best-effort: failures to create the cache directory or write the entry are swallowed, since
+    /// the cache is purely a speed optimization and should never cause a load to fail.
+    pub(crate) fn put(&self, src: &str, entry: CachedSource) {
+        if fs::create_dir_all(&self.root).is_err() {
+            return;
+        }
+        if let Ok(serialized) = serde_json::to_vec(&entry) {
+            let _ = fs::write(self.path_for(src), serialized);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// A fresh, empty cache directory under the system temp dir, unique to this test run so
+    /// parallel tests never share (or race on) the same files.
+    fn test_cache() -> (SourceCache, PathBuf) {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let root = std::env::temp_dir().join(format!(
+            "polar-source-cache-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        (SourceCache::new(root.clone()), root)
+    }
+
+    fn entry(validation_error: Option<&str>) -> CachedSource {
+        CachedSource {
+            rules: vec![],
+            rule_prototypes: vec![],
+            validation_error: validation_error.map(str::to_owned),
+        }
+    }
+
+    #[test]
+    fn test_round_trip_hit() {
+        let (cache, root) = test_cache();
+        cache.put("f(x) if x = 1;", entry(Some("oops")));
+
+        let cached = cache.get("f(x) if x = 1;").expect("put entry should hit");
+        assert_eq!(cached.validation_error.as_deref(), Some("oops"));
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_miss_for_unwritten_source() {
+        let (cache, root) = test_cache();
+        assert!(cache.get("never written").is_none());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_corrupt_entry_is_a_miss() {
+        let (cache, root) = test_cache();
+        cache.put("f(x) if x = 1;", entry(None));
+        fs::write(cache.path_for("f(x) if x = 1;"), b"not valid json").unwrap();
+
+        assert!(cache.get("f(x) if x = 1;").is_none());
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_version_bump_invalidates_cache() {
+        let (cache, root) = test_cache();
+        let src = "f(x) if x = 1;";
+
+        // Write an entry as if it came from a different `CACHE_FORMAT_VERSION` by hashing it by
+        // hand with a version this build doesn't use, bypassing `key_for`.
+        let mut hasher = DefaultHasher::new();
+        (CACHE_FORMAT_VERSION + 1).hash(&mut hasher);
+        src.hash(&mut hasher);
+        let stale_path = root.join(format!("{:016x}.json", hasher.finish()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(&stale_path, serde_json::to_vec(&entry(None)).unwrap()).unwrap();
+
+        // The current version's key never points at the stale entry, so it's a miss rather than
+        // an accidental hit on data written under a different cache format.
+        assert!(cache.get(src).is_none());
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
@@ -2,19 +2,14 @@ use std::collections::{HashMap, HashSet};
 use std::ops::Range;
 
 use lalrpop_util::ParseError as LalrpopError;
+use serde::{Deserialize, Serialize};
 
-use super::error::{ParseError, PolarError, PolarResult, RuntimeError};
+use super::error::{OperationalError, ParseError, PolarError, PolarResult, RuntimeError};
 use super::kb::KnowledgeBase;
 use super::lexer::Token;
 use super::rules::*;
 use super::terms::*;
 
-// TODO(gj): if a user imports the built-in rule prototypes, we should emit an error if the user
-// hasn't registered at least a single Actor and Resource type by the time loading is complete.
-// Maybe only if they've defined at least one rule matching one of the rule prototypes? Otherwise,
-// the rule prototypes will always trigger. But maybe their error message will be descriptive
-// enough as-is?
-
 // TODO(gj): round up longhand `has_permission/3` and `has_role/3` rules to incorporate their
 // referenced permissions & roles (implied & implier side) into the exhaustiveness checks.
 
@@ -31,6 +26,10 @@ pub enum Production {
     Permissions(Term),                       // List<String>
     Relations(Term),                         // Dict<Symbol, Symbol>
     Implication(Term, (Term, Option<Term>)), // (String, (String, Option<String>))
+    /// An `extends` declaration, where `Term` is the `Symbol` naming a declaration set registered
+    /// via `ResourceBlocks::declare_set`. The referenced set's roles and permissions get merged
+    /// into this block's own at `ResourceBlock::add_to_kb` time, as if they'd been written inline.
+    Extends(Term),
 }
 
 pub fn validate_relation_keyword(
@@ -63,6 +62,7 @@ pub fn validate_parsed_declaration(
         ("roles", Value::List(_)) => Ok(Production::Roles(term)),
         ("permissions", Value::List(_)) => Ok(Production::Permissions(term)),
         ("relations", Value::Dictionary(_)) => Ok(Production::Relations(term)),
+        ("extends", Value::Variable(_)) => Ok(Production::Extends(term)),
 
         ("roles", Value::Dictionary(_)) | ("permissions", Value::Dictionary(_)) => {
             let (loc, ranges) = (term.offset(), vec![term_source_range(&term)]);
@@ -123,6 +123,7 @@ pub fn turn_productions_into_resource_block(
         let mut permissions: Option<Term> = None;
         let mut relations: Option<Term> = None;
         let mut implications = vec![];
+        let mut extends = vec![];
 
         let make_error = |name: &str, previous: &Term, new: &Term| {
             let loc = new.offset();
@@ -162,6 +163,7 @@ pub fn turn_productions_into_resource_block(
                     // TODO(gj): Warn the user on duplicate implication definitions.
                     implications.push(Implication { head, body });
                 }
+                Production::Extends(name) => extends.push(name),
             }
         }
 
@@ -172,6 +174,7 @@ pub fn turn_productions_into_resource_block(
             permissions,
             relations,
             implications,
+            extends,
         })
     } else {
         let (loc, ranges) = (resource.offset(), vec![]);
@@ -181,7 +184,7 @@ pub fn turn_productions_into_resource_block(
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Declaration {
     Role,
     Permission,
@@ -189,7 +192,7 @@ pub enum Declaration {
     Relation(Term),
 }
 
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Implication {
     /// `Term` is a `String`. E.g., `"member"` in `"member" if "owner";`.
     pub head: Term,
@@ -240,13 +243,13 @@ impl Declaration {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum EntityType {
     Actor,
     Resource,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ResourceBlock {
     pub entity_type: EntityType,
     pub resource: Term,
@@ -254,15 +257,73 @@ pub struct ResourceBlock {
     pub permissions: Option<Term>,
     pub relations: Option<Term>,
     pub implications: Vec<Implication>,
+    /// Names of declaration sets (registered via `ResourceBlocks::declare_set`) pulled in with
+    /// `extends`. Merged into `roles`/`permissions` in `add_to_kb`, as if their contents had
+    /// been written inline in this block.
+    pub extends: Vec<Term>,
+}
+
+/// A reusable, named set of role/permission declarations that a resource block can pull in via
+/// `extends` instead of re-listing the same strings. Registered only with
+/// `ResourceBlocks::declare_set`; there is no policy syntax that declares one.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DeclarationSet {
+    pub roles: Option<Term>,
+    pub permissions: Option<Term>,
+}
+
+/// One entry in a materialized grant closure (see `ResourceBlocks::materialize_closure`): a
+/// role/permission that, if held, grants some other term. A local implication (`"writer" if
+/// "owner";`) produces an edge with `relation: None` and `resource` equal to the original term's
+/// own resource; a relation-qualified one (`"writer" if "owner" on "parent";`) instead points
+/// `resource` at the relation's target block and records the relation that connects them.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GrantEdge {
+    /// The resource block the granting role/permission is checked against.
+    pub resource: Term,
+    /// The role or permission name that, if held on `resource`, grants the original term.
+    pub term: Term,
+    /// The relation of the implication that produced this edge; `None` if that implication is
+    /// local. An edge folded in transitively keeps the relation of its own hop, so it can be
+    /// `None` even when `resource` is a different block than the original term's.
+    pub relation: Option<Term>,
 }
 
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct ResourceBlocks {
     /// Map from resource (`Symbol`) to the declarations for that resource.
     declarations: HashMap<Term, Declarations>,
     pub implications: HashMap<Term, Vec<Implication>>,
     pub actors: HashSet<Term>,
     pub resources: HashSet<Term>,
+    /// Strongly connected components of the cross-resource `relations` graph, each a cycle of
+    /// mutually (or self-) related resource blocks. Populated by `check_for_relation_cycles`.
+    /// Exposed so a future tabled evaluation mode can consult which rewritten rules need tabling
+    /// to terminate.
+    pub relation_cycles: Vec<Vec<Term>>,
+    /// Map from declaration set name to its contents, registered via `declare_set` and pulled
+    /// into a block's own roles/permissions via `extends`.
+    declaration_sets: HashMap<Symbol, DeclarationSet>,
+    /// Transitive grant closure, materialized by `materialize_closure` and invalidated (by `add`)
+    /// whenever a new block comes in. `None` until the first call to `materialize_closure`.
+    #[serde(skip)]
+    closure: Option<HashMap<(Term, Term), HashSet<GrantEdge>>>,
+}
+
+/// Bump whenever `ResourceBlocks`' on-disk shape (or the semantics of what gets cached) changes, so
+/// a cache written by an older `oso` version is rejected as stale instead of corrupting a reload.
+/// Mirrors `source_cache::CACHE_FORMAT_VERSION`, but versioned independently since the two caches
+/// serialize different things on different schedules.
+const RESOURCE_BLOCKS_CACHE_FORMAT_VERSION: u64 = 1;
+
+/// On-disk envelope for `ResourceBlocks::dump`/`load`. Wraps the serialized blocks in a format
+/// version plus a hash of the payload, so a stale or corrupted cache is rejected outright instead
+/// of silently rehydrating into a `KnowledgeBase` that doesn't match what was actually validated.
+#[derive(Serialize, Deserialize)]
+struct ResourceBlocksCache {
+    format_version: u64,
+    hash: u64,
+    payload: Vec<u8>,
 }
 
 impl ResourceBlocks {
@@ -272,6 +333,9 @@ impl ResourceBlocks {
             implications: HashMap::new(),
             actors: HashSet::new(),
             resources: HashSet::new(),
+            relation_cycles: Vec::new(),
+            declaration_sets: HashMap::new(),
+            closure: None,
         }
     }
 
@@ -280,6 +344,16 @@ impl ResourceBlocks {
         self.implications.clear();
         self.actors.clear();
         self.resources.clear();
+        self.relation_cycles.clear();
+        self.declaration_sets.clear();
+        self.closure = None;
+    }
+
+    /// Register a named, reusable declaration set that a resource block can later pull in
+    /// wholesale via `extends` instead of re-listing the same role/permission strings. This is the
+    /// only way to register one; policy text has no syntax for declaring a set.
+    pub fn declare_set(&mut self, name: Symbol, set: DeclarationSet) {
+        self.declaration_sets.insert(name, set);
     }
 
     fn add(
@@ -295,6 +369,9 @@ impl ResourceBlocks {
             EntityType::Actor => self.actors.insert(resource),
             EntityType::Resource => self.resources.insert(resource),
         };
+        // A new block may add implications that feed into an existing grant chain, so any
+        // previously materialized closure is now stale.
+        self.closure = None;
     }
 
     fn exists(&self, resource: &Term) -> bool {
@@ -364,6 +441,202 @@ impl ResourceBlocks {
             Err(ParseError::ParseSugar { loc, msg, ranges }.into())
         }
     }
+
+    /// Expand every loaded resource block's `implications` into the `Rule`s `Implication::as_rule`
+    /// rewrites them to -- the same rules `KnowledgeBase::rewrite_implications` would add to the
+    /// rule base, but grouped by originating resource block and handed back read-only instead of
+    /// consumed, as a HIR-style view for introspection tooling (e.g. a host binding printing "this
+    /// `resource Repo` block expands to these N rules"). Each rule retains its source span (from
+    /// `Rule::new_from_parser`, via the implication's head), so a host can point a user at exactly
+    /// the `... if ...;` line that produced it.
+    pub fn desugar(&self) -> PolarResult<Vec<ResourceBlockExpansion>> {
+        let mut expansions = vec![];
+        for (resource, implications) in &self.implications {
+            let mut rules = vec![];
+            for implication in implications {
+                rules.push(implication.as_rule(resource, self)?);
+            }
+            expansions.push(ResourceBlockExpansion {
+                resource: resource.clone(),
+                rules,
+            });
+        }
+        Ok(expansions)
+    }
+
+    /// Build, for every `(resource, term)` pair with at least one implication, the full
+    /// transitively reachable set of `GrantEdge`s that grant it -- e.g. a permission granted by a
+    /// role, itself granted by another role across a `parent` relation -- so a host can answer
+    /// "what grants this?" via `grants_for` in O(1) instead of re-walking the implication graph on
+    /// every query. Call once after every block has been added (`KnowledgeBase::rewrite_implications`
+    /// does this); `add` invalidates the cached result, so it must be recomputed after loading more
+    /// policy.
+    ///
+    /// Computed by repeated relaxation to a fixed point rather than a recursive walk, sharing its
+    /// cycle-safety with `check_for_circular_implications`/`check_for_relation_cycles`: a grant
+    /// never folds its own `(resource, term)` key into its own set, so a cycle in the implication
+    /// graph just stops contributing once every edge along it has been folded in once, and
+    /// relaxation still terminates.
+    pub fn materialize_closure(&mut self) {
+        let mut closure: HashMap<(Term, Term), HashSet<GrantEdge>> = HashMap::new();
+
+        for (resource, implications) in &self.implications {
+            for implication in implications {
+                let edge = match &implication.body.1 {
+                    None => GrantEdge {
+                        resource: resource.clone(),
+                        term: implication.body.0.clone(),
+                        relation: None,
+                    },
+                    Some(relation) => {
+                        let target = self
+                            .get_relation_type_in_resource_block(relation, resource)
+                            .map(Clone::clone)
+                            .unwrap_or_else(|_| resource.clone());
+                        GrantEdge {
+                            resource: target,
+                            term: implication.body.0.clone(),
+                            relation: Some(relation.clone()),
+                        }
+                    }
+                };
+                closure
+                    .entry((resource.clone(), implication.head.clone()))
+                    .or_default()
+                    .insert(edge);
+            }
+        }
+
+        loop {
+            let mut grew = false;
+            for key in closure.keys().cloned().collect::<Vec<_>>() {
+                let transitive_keys: Vec<(Term, Term)> = closure[&key]
+                    .iter()
+                    .map(|edge| (edge.resource.clone(), edge.term.clone()))
+                    .filter(|transitive_key| transitive_key != &key)
+                    .collect();
+                let mut additions = vec![];
+                for transitive_key in transitive_keys {
+                    if let Some(transitive) = closure.get(&transitive_key) {
+                        additions.extend(
+                            transitive
+                                .iter()
+                                .filter(|edge| !closure[&key].contains(*edge))
+                                .cloned(),
+                        );
+                    }
+                }
+                if !additions.is_empty() {
+                    grew = true;
+                    closure.get_mut(&key).unwrap().extend(additions);
+                }
+            }
+            if !grew {
+                break;
+            }
+        }
+
+        self.closure = Some(closure);
+    }
+
+    /// Look up the precomputed grant closure for `term` on `resource` (see `materialize_closure`).
+    /// Returns `None` if the closure hasn't been (re)computed since the last block was added, or if
+    /// `(resource, term)` has no implications granting it.
+    pub fn grants_for(&self, resource: &Term, term: &Term) -> Option<&HashSet<GrantEdge>> {
+        self.closure
+            .as_ref()?
+            .get(&(resource.clone(), term.clone()))
+    }
+
+    /// Take the materialized closure out of `self`, leaving `None` behind. Used by
+    /// `KnowledgeBase::rewrite_implications` to move the result onto the `KnowledgeBase` itself
+    /// before `self` (the transient `resource_blocks` scratch space) is cleared.
+    pub fn take_closure(&mut self) -> HashMap<(Term, Term), HashSet<GrantEdge>> {
+        self.closure.take().unwrap_or_default()
+    }
+
+    /// Serialize this (already parsed-and-validated) set of resource blocks to a compact binary
+    /// blob, so a host can persist it after a successful `load_str`/`load_file` and rehydrate it
+    /// directly via `load` on the next boot -- skipping `index_declarations` and every validation
+    /// pass in `add_to_kb` -- instead of re-parsing the same policy text from scratch every time a
+    /// short-lived process starts up. The materialized grant closure (`materialize_closure`) is
+    /// deliberately left out: it's cheap to recompute and leaving it out keeps this format stable
+    /// even as the closure's own internals change.
+    pub fn dump(&self) -> PolarResult<Vec<u8>> {
+        let mut blocks = self.clone();
+        blocks.closure = None;
+        let payload = bincode::serialize(&blocks).map_err(|e| {
+            OperationalError::InvalidState(format!(
+                "Failed to serialize resource blocks cache: {}",
+                e
+            ))
+            .into()
+        })?;
+        let hash = Self::hash_bytes(&payload);
+        let cache = ResourceBlocksCache {
+            format_version: RESOURCE_BLOCKS_CACHE_FORMAT_VERSION,
+            hash,
+            payload,
+        };
+        bincode::serialize(&cache).map_err(|e| {
+            OperationalError::InvalidState(format!(
+                "Failed to serialize resource blocks cache envelope: {}",
+                e
+            ))
+            .into()
+        })
+    }
+
+    /// Rehydrate a `ResourceBlocks` previously produced by `dump`. Rejects the blob outright --
+    /// rather than silently loading something stale or corrupt into a `KnowledgeBase` -- if it was
+    /// written by a different `RESOURCE_BLOCKS_CACHE_FORMAT_VERSION`, or if its embedded hash
+    /// doesn't match its payload.
+    pub fn load(bytes: &[u8]) -> PolarResult<Self> {
+        let cache: ResourceBlocksCache = bincode::deserialize(bytes).map_err(|e| {
+            OperationalError::InvalidState(format!("Failed to read resource blocks cache: {}", e))
+                .into()
+        })?;
+        if cache.format_version != RESOURCE_BLOCKS_CACHE_FORMAT_VERSION {
+            return Err(OperationalError::InvalidState(format!(
+                "Resource blocks cache was written by a different format version ({} != {})",
+                cache.format_version, RESOURCE_BLOCKS_CACHE_FORMAT_VERSION,
+            ))
+            .into());
+        }
+        if Self::hash_bytes(&cache.payload) != cache.hash {
+            return Err(OperationalError::InvalidState(
+                "Resource blocks cache failed its integrity check -- the payload doesn't match its \
+                own hash."
+                    .to_owned(),
+            )
+            .into());
+        }
+        bincode::deserialize(&cache.payload).map_err(|e| {
+            OperationalError::InvalidState(format!(
+                "Failed to deserialize resource blocks cache: {}",
+                e
+            ))
+            .into()
+        })
+    }
+
+    fn hash_bytes(bytes: &[u8]) -> u64 {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// One resource block's HIR-style projection onto the rewritten `Rule`s it expands into. Returned
+/// by `ResourceBlocks::desugar` / `KnowledgeBase::desugar_resource_blocks`.
+#[derive(Clone, Debug)]
+pub struct ResourceBlockExpansion {
+    /// The resource block's own type, e.g. `Repo` in `resource Repo { ... }`.
+    pub resource: Term,
+    /// Every rule this block's implications rewrite to, in declaration order.
+    pub rules: Vec<Rule>,
 }
 
 pub fn check_all_relation_types_have_been_registered(kb: &KnowledgeBase) -> Vec<PolarError> {
@@ -378,6 +651,137 @@ pub fn check_all_relation_types_have_been_registered(kb: &KnowledgeBase) -> Vec<
     errors
 }
 
+/// Lint-level checks for declared-but-unreachable or declared-but-unimplemented pieces of the
+/// loaded resource blocks, per the exhaustiveness TODOs above. Unlike
+/// `check_all_relation_types_have_been_registered`, none of these stop a policy from loading --
+/// a permission nobody ever grants, a role that implies nothing, or a relation with no
+/// `has_relation/3` rule to back it are usually typos or dead policy, but it's up to the host to
+/// decide whether to surface them (e.g. as warnings). The relation check only looks at whether
+/// any `has_relation` rule is loaded at all: once one is, no relation is flagged, and whether it
+/// covers a particular relation is not checked.
+pub fn check_declaration_exhaustiveness(kb: &KnowledgeBase) -> Vec<PolarError> {
+    let blocks = &kb.resource_blocks;
+    let mut errors = vec![];
+
+    // Every (resource, term) pair used as an implication head (something an implication grants)
+    // or as an implier (something that, once held, grants something else). An implier resolved
+    // through a relation (`"writer" if "owner" on "parent";`) is attributed to the *related*
+    // resource block, since that's where it's actually declared.
+    let mut heads_used = HashSet::new();
+    let mut impliers_used = HashSet::new();
+    for (resource, implications) in &blocks.implications {
+        for implication in implications {
+            heads_used.insert((resource.clone(), implication.head.clone()));
+            match &implication.body.1 {
+                None => {
+                    impliers_used.insert((resource.clone(), implication.body.0.clone()));
+                }
+                Some(relation) => {
+                    if let Ok(target) =
+                        blocks.get_relation_type_in_resource_block(relation, resource)
+                    {
+                        impliers_used.insert((target.clone(), implication.body.0.clone()));
+                    }
+                }
+            }
+        }
+    }
+
+    let has_relation_rule_exists = kb.get_rules().contains_key(&sym!("has_relation"));
+
+    for (resource, declarations) in &blocks.declarations {
+        for (term, declaration) in declarations {
+            match declaration {
+                Declaration::Permission => {
+                    if !heads_used.contains(&(resource.clone(), term.clone())) {
+                        let msg = format!(
+                            "Permission {} declared in the '{}' resource block is never granted \
+                            -- it doesn't appear as the head of any implication.",
+                            term.to_polar(),
+                            resource.to_polar(),
+                        );
+                        let (loc, ranges) = (term.offset(), vec![]);
+                        errors.push(ParseError::ParseSugar { loc, msg, ranges }.into());
+                    }
+                }
+                Declaration::Role => {
+                    if !impliers_used.contains(&(resource.clone(), term.clone())) {
+                        let msg = format!(
+                            "Role {} declared in the '{}' resource block is never used as an \
+                            implier -- holding it doesn't imply anything.",
+                            term.to_polar(),
+                            resource.to_polar(),
+                        );
+                        let (loc, ranges) = (term.offset(), vec![]);
+                        errors.push(ParseError::ParseSugar { loc, msg, ranges }.into());
+                    }
+                }
+                Declaration::Relation(_) => {
+                    if !has_relation_rule_exists {
+                        let msg = format!(
+                            "Relation {} declared in the '{}' resource block has no \
+                            corresponding `has_relation/3` rule -- traversals through it will \
+                            always fail.",
+                            term.to_polar(),
+                            resource.to_polar(),
+                        );
+                        let (loc, ranges) = (term.offset(), vec![]);
+                        errors.push(ParseError::ParseSugar { loc, msg, ranges }.into());
+                    }
+                }
+            }
+        }
+    }
+    errors
+}
+
+/// Concatenate an `extends`-pulled-in list of roles or permissions onto a block's own (in that
+/// order, local first), so implications in the importing block rewrite exactly as if the imported
+/// names had been written inline. Falls back to whichever side is present if the other is absent.
+fn merge_declaration_lists(local: Option<Term>, imported: Option<&Term>) -> PolarResult<Option<Term>> {
+    Ok(match (local, imported) {
+        (Some(local), Some(imported)) => {
+            let mut items = local.value().as_list()?.clone();
+            items.extend(imported.value().as_list()?.iter().cloned());
+            Some(local.clone_with_value(value!(items)))
+        }
+        (Some(local), None) => Some(local),
+        (None, Some(imported)) => Some(imported.clone()),
+        (None, None) => None,
+    })
+}
+
+/// Check a block's locally-declared roles/permissions against a set it's about to `extends` for
+/// collisions. Reported separately from the purely-local duplicate check in `index_declarations`,
+/// since the map built there only has one occurrence's source location to point at once it's
+/// overwritten the other -- here we still have both original terms, so the diagnostic can point at
+/// both.
+fn check_for_duplicates_with_extended_set(
+    kind: &'static str,
+    local: Option<&Term>,
+    imported: Option<&Term>,
+    resource: &Term,
+) -> PolarResult<()> {
+    let (local, imported) = match (local, imported) {
+        (Some(local), Some(imported)) => (local, imported),
+        _ => return Ok(()),
+    };
+    for local_item in local.value().as_list()? {
+        for imported_item in imported.value().as_list()? {
+            if local_item.value() == imported_item.value() {
+                return Err(ValidationError::DuplicateDeclaration {
+                    term: local_item.clone(),
+                    resource: resource.clone(),
+                    kind,
+                    extended_from: Some(imported_item.clone()),
+                }
+                .into());
+            }
+        }
+    }
+    Ok(())
+}
+
 fn index_declarations(
     roles: Option<Term>,
     permissions: Option<Term>,
@@ -392,13 +796,13 @@ fn index_declarations(
                 .insert(role.clone(), Declaration::Role)
                 .is_some()
             {
-                let (loc, ranges) = (role.offset(), vec![]);
-                let msg = format!(
-                    "{}: Duplicate declaration of {} in the roles list.",
-                    resource.to_polar(),
-                    role.to_polar()
-                );
-                return Err(ParseError::ParseSugar { loc, msg, ranges }.into());
+                return Err(ValidationError::DuplicateDeclaration {
+                    term: role.clone(),
+                    resource: resource.clone(),
+                    kind: "roles",
+                    extended_from: None,
+                }
+                .into());
             }
         }
     }
@@ -407,21 +811,22 @@ fn index_declarations(
         for permission in permissions.value().as_list()? {
             if let Some(previous) = declarations.insert(permission.clone(), Declaration::Permission)
             {
-                let msg = if matches!(previous, Declaration::Permission) {
-                    format!(
-                        "{}: Duplicate declaration of {} in the permissions list.",
-                        resource.to_polar(),
-                        permission.to_polar()
-                    )
+                let error = if matches!(previous, Declaration::Permission) {
+                    ValidationError::DuplicateDeclaration {
+                        term: permission.clone(),
+                        resource: resource.clone(),
+                        kind: "permissions",
+                        extended_from: None,
+                    }
                 } else {
-                    format!(
-                        "{}: {} declared as a permission but it was previously declared as a role.",
-                        resource.to_polar(),
-                        permission.to_polar()
-                    )
+                    ValidationError::ClashingDeclaration {
+                        term: permission.clone(),
+                        resource: resource.clone(),
+                        this_kind: "permission",
+                        prior_kind: "role",
+                    }
                 };
-                let (loc, ranges) = (permission.offset(), vec![]);
-                return Err(ParseError::ParseSugar { loc, msg, ranges }.into());
+                return Err(error.into());
             }
         }
     }
@@ -435,21 +840,18 @@ fn index_declarations(
             let stringified_relation = relation_type.clone_with_value(value!(relation.0.as_str()));
             let declaration = Declaration::Relation(relation_type.clone());
             if let Some(previous) = declarations.insert(stringified_relation, declaration) {
-                let msg = match previous {
-                    Declaration::Role => format!(
-                        "{}: '{}' declared as a relation but it was previously declared as a role.",
-                        resource.to_polar(),
-                        relation.to_polar()
-                    ),
-                    Declaration::Permission => format!(
-                        "{}: '{}' declared as a relation but it was previously declared as a permission.",
-                        resource.to_polar(),
-                        relation.to_polar()
-                    ),
-                    _ => unreachable!("duplicate dict keys aren't parseable"),
+                let prior_kind = match previous {
+                    Declaration::Role => "role",
+                    Declaration::Permission => "permission",
+                    Declaration::Relation(_) => unreachable!("duplicate dict keys aren't parseable"),
                 };
-                let (loc, ranges) = (relation_type.offset(), vec![]);
-                return Err(ParseError::ParseSugar { loc, msg, ranges }.into());
+                return Err(ValidationError::ClashingDeclaration {
+                    term: relation_type.clone_with_value(value!(sym!(relation.0.as_str()))),
+                    resource: resource.clone(),
+                    this_kind: "relation",
+                    prior_kind,
+                }
+                .into());
             }
         }
     }
@@ -545,7 +947,9 @@ fn implication_head_to_params(head: &Term, resource: &Term) -> Vec<Parameter> {
     vec![
         Parameter {
             parameter: head.clone_with_value(value!(sym!("actor"))),
-            specializer: Some(head.clone_with_value(value!(pattern!(instance!("Actor"))))),
+            specializer: Some(
+                head.clone_with_value(value!(pattern!(instance!(ACTOR_UNION_NAME)))),
+            ),
         },
         Parameter {
             parameter: head.clone(),
@@ -575,6 +979,203 @@ fn check_for_duplicate_resource_blocks(
     Ok(())
 }
 
+/// Synthesize the `Actor` and `Resource` union types from the actor/resource blocks loaded so
+/// far and register them in `kb`: each union becomes a registered class whose MRO every member
+/// block is made a subclass of, so a specializer against the union (e.g. the `actor: Actor`
+/// specializer `implication_head_to_params` puts on every rewritten `has_role`/`has_permission`
+/// rule) matches an instance of any concrete actor/resource block rather than a single literal
+/// class. A no-op if no resource blocks were loaded at all.
+///
+/// Emits a "no actor/resource type registered" error if
+/// resource blocks were loaded but only one side of the union has any members -- the built-in
+/// `has_role`/`has_permission` rule prototypes would otherwise always fail to match anything.
+pub fn register_union_types(
+    kb: &mut KnowledgeBase,
+    actors: &HashSet<Term>,
+    resources: &HashSet<Term>,
+) -> PolarResult<()> {
+    if actors.is_empty() && resources.is_empty() {
+        return Ok(());
+    }
+    if actors.is_empty() || resources.is_empty() {
+        let msg = "Resource blocks are in use, but no resource block declares an actor (e.g. \
+            `actor User {}`) or no resource block declares a non-actor resource (e.g. `resource \
+            Repo {}`). At least one of each is required to use the built-in `has_role`/\
+            `has_permission` rules."
+            .to_owned();
+        return Err(ParseError::ParseSugar {
+            loc: 0,
+            msg,
+            ranges: vec![],
+        }
+        .into());
+    }
+    register_union_type(kb, ACTOR_UNION_NAME, actors)?;
+    register_union_type(kb, RESOURCE_UNION_NAME, resources)?;
+    Ok(())
+}
+
+/// Register a single synthesized union class named `union_name` and make every term in
+/// `members` a subclass of it via `KnowledgeBase::extend_mro`.
+fn register_union_type(
+    kb: &mut KnowledgeBase,
+    union_name: &str,
+    members: &HashSet<Term>,
+) -> PolarResult<()> {
+    let union_symbol = Symbol::new(union_name);
+    let union_instance_id = kb.new_id();
+    kb.constant(
+        union_symbol.clone(),
+        term!(Value::ExternalInstance(ExternalInstance {
+            instance_id: union_instance_id,
+            constructor: None,
+            repr: None,
+        })),
+    );
+    kb.add_mro(union_symbol, vec![union_instance_id])?;
+    for member in members {
+        let member_symbol = member.value().as_symbol()?.clone();
+        kb.extend_mro(&member_symbol, union_instance_id)?;
+    }
+    Ok(())
+}
+
+/// A resource-block validation failure, structured so a host binding or editor integration can
+/// pattern-match on the failure category (e.g. offer a "register this class" quick-fix) instead of
+/// scraping an English message out of a generic parse error. `Display` renders the same messages
+/// these checks produced back when they were all shoehorned into `ParseError::ParseSugar`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    /// A resource block names a class that was never registered, e.g. `resource Org {}` with no
+    /// `Org` constant in scope.
+    UnregisteredClass { name: Term },
+    /// An implication references a term that was never declared as a role, permission, or
+    /// relation in `resource`'s block.
+    UndeclaredTerm { term: Term, resource: Term },
+    /// `term` was declared twice as the same kind of thing (role, permission, or relation) --
+    /// either twice in the same list, or once locally and once via an `extends`-ed declaration
+    /// set, in which case `extended_from` carries the other declaration's term for its location.
+    DuplicateDeclaration {
+        term: Term,
+        resource: Term,
+        kind: &'static str,
+        extended_from: Option<Term>,
+    },
+    /// `term` was declared as `this_kind` but had already been declared as `prior_kind`.
+    ClashingDeclaration {
+        term: Term,
+        resource: Term,
+        this_kind: &'static str,
+        prior_kind: &'static str,
+    },
+    /// A relation's target type was never registered as a class, e.g. `parent: Org` with no `Org`
+    /// constant in scope.
+    UnregisteredRelationType { relation: Term, kind: Term },
+}
+
+impl ValidationError {
+    /// The offset a host binding or editor integration should point a user at for this error.
+    pub fn loc(&self) -> usize {
+        match self {
+            ValidationError::UnregisteredClass { name } => name.offset(),
+            ValidationError::UndeclaredTerm { term, .. } => term.offset(),
+            ValidationError::DuplicateDeclaration { term, .. } => term.offset(),
+            ValidationError::ClashingDeclaration { term, .. } => term.offset(),
+            ValidationError::UnregisteredRelationType { relation, .. } => relation.offset(),
+        }
+    }
+
+    /// Extra source ranges worth highlighting alongside `loc`, e.g. both sites of a duplicate
+    /// declaration pulled in via `extends`. Empty when `loc` alone is enough context.
+    pub fn ranges(&self) -> Vec<Range<usize>> {
+        match self {
+            ValidationError::DuplicateDeclaration {
+                term,
+                extended_from: Some(other),
+                ..
+            } => vec![term_source_range(other), term_source_range(term)],
+            _ => vec![],
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ValidationError::UnregisteredClass { name } => write!(
+                f,
+                "Invalid resource block '{}' -- '{}' must be a registered class.",
+                name.to_polar(),
+                name.to_polar(),
+            ),
+            ValidationError::UndeclaredTerm { term, resource } => write!(
+                f,
+                "Undeclared term {} referenced in rule in '{}' resource block. \
+                Did you mean to declare it as a role, permission, or relation?",
+                term.to_polar(),
+                resource,
+            ),
+            ValidationError::DuplicateDeclaration {
+                term,
+                resource,
+                kind,
+                extended_from,
+            } => match extended_from {
+                Some(_) => write!(
+                    f,
+                    "{}: Duplicate declaration of {} in the {} list -- it's already pulled in via 'extends'.",
+                    resource.to_polar(),
+                    term.to_polar(),
+                    kind,
+                ),
+                None => write!(
+                    f,
+                    "{}: Duplicate declaration of {} in the {} list.",
+                    resource.to_polar(),
+                    term.to_polar(),
+                    kind,
+                ),
+            },
+            ValidationError::ClashingDeclaration {
+                term,
+                resource,
+                this_kind,
+                prior_kind,
+            } => {
+                // Relation names are plain symbols (e.g. `parent`), so -- unlike role/permission
+                // terms, which are already-quoted strings -- they need explicit quotes to stand out
+                // in the message.
+                let term_repr = if *this_kind == "relation" {
+                    format!("'{}'", term.to_polar())
+                } else {
+                    term.to_polar()
+                };
+                write!(
+                    f,
+                    "{}: {} declared as a {} but it was previously declared as a {}.",
+                    resource.to_polar(),
+                    term_repr,
+                    this_kind,
+                    prior_kind,
+                )
+            }
+            ValidationError::UnregisteredRelationType { relation, kind } => write!(
+                f,
+                "Type '{}' in relation '{}: {}' must be registered as a class.",
+                kind.to_polar(),
+                relation.value().as_string().unwrap_or(""),
+                kind.to_polar(),
+            ),
+        }
+    }
+}
+
+impl From<ValidationError> for PolarError {
+    fn from(error: ValidationError) -> Self {
+        super::error::ErrorKind::Validation(error).into()
+    }
+}
+
 // TODO(gj): no way to know in the core if `term` was registered as a class or a constant.
 fn is_registered_class(kb: &KnowledgeBase, term: &Term) -> PolarResult<bool> {
     Ok(kb.is_constant(term.value().as_symbol()?))
@@ -585,15 +1186,10 @@ fn check_that_block_resource_is_registered_as_a_class(
     resource: &Term,
 ) -> PolarResult<()> {
     if !is_registered_class(kb, resource)? {
-        // TODO(gj): better error message
-        let msg = format!(
-            "Invalid resource block '{}' -- '{}' must be a registered class.",
-            resource.to_polar(),
-            resource.to_polar(),
-        );
-        let (loc, ranges) = (resource.offset(), vec![]);
-        // TODO(gj): UnregisteredClassError in the core.
-        return Err(ParseError::ParseSugar { loc, msg, ranges }.into());
+        return Err(ValidationError::UnregisteredClass {
+            name: resource.clone(),
+        }
+        .into());
     }
     Ok(())
 }
@@ -603,19 +1199,160 @@ fn relation_type_is_registered(
     (relation, kind): (&Term, &Term),
 ) -> PolarResult<()> {
     if !is_registered_class(kb, kind)? {
-        let msg = format!(
-            "Type '{}' in relation '{}: {}' must be registered as a class.",
-            kind.to_polar(),
-            relation.value().as_string()?,
-            kind.to_polar(),
-        );
-        let (loc, ranges) = (relation.offset(), vec![]);
-        // TODO(gj): UnregisteredClassError in the core.
-        return Err(ParseError::ParseSugar { loc, msg, ranges }.into());
+        return Err(ValidationError::UnregisteredRelationType {
+            relation: relation.clone(),
+            kind: kind.clone(),
+        }
+        .into());
     }
     Ok(())
 }
 
+/// Build the cross-resource relation graph: one edge `resource --relation_name--> relation_type`
+/// for every `relation_name: relation_type` declared in each resource block's `relations` dict.
+fn relation_graph(blocks: &ResourceBlocks) -> HashMap<Term, Vec<(String, Term)>> {
+    let mut graph = HashMap::new();
+    for (resource, declarations) in &blocks.declarations {
+        let mut edges = vec![];
+        for (name, declaration) in declarations {
+            if let Declaration::Relation(relation_type) = declaration {
+                if let Ok(name) = name.value().as_string() {
+                    edges.push((name.to_owned(), relation_type.clone()));
+                }
+            }
+        }
+        graph.insert(resource.clone(), edges);
+    }
+    graph
+}
+
+/// Tarjan's strongly-connected-components algorithm over the cross-resource relation graph.
+/// Returns one component per maximal set of resource blocks that are mutually reachable from one
+/// another via declared relations, including single-block components that only reach themselves
+/// via a self-loop (e.g. `Dir { relations = { parent: Dir }; }`).
+fn strongly_connected_components(graph: &HashMap<Term, Vec<(String, Term)>>) -> Vec<Vec<Term>> {
+    struct Tarjan<'a> {
+        graph: &'a HashMap<Term, Vec<(String, Term)>>,
+        next_index: usize,
+        stack: Vec<Term>,
+        on_stack: HashSet<Term>,
+        index: HashMap<Term, usize>,
+        lowlink: HashMap<Term, usize>,
+        components: Vec<Vec<Term>>,
+    }
+
+    impl<'a> Tarjan<'a> {
+        fn visit(&mut self, node: &Term) {
+            self.index.insert(node.clone(), self.next_index);
+            self.lowlink.insert(node.clone(), self.next_index);
+            self.next_index += 1;
+            self.stack.push(node.clone());
+            self.on_stack.insert(node.clone());
+
+            for (_, successor) in self.graph.get(node).into_iter().flatten() {
+                if !self.index.contains_key(successor) {
+                    self.visit(successor);
+                    let lowlink = self.lowlink[node].min(self.lowlink[successor]);
+                    self.lowlink.insert(node.clone(), lowlink);
+                } else if self.on_stack.contains(successor) {
+                    let lowlink = self.lowlink[node].min(self.index[successor]);
+                    self.lowlink.insert(node.clone(), lowlink);
+                }
+            }
+
+            if self.lowlink[node] == self.index[node] {
+                let mut component = vec![];
+                loop {
+                    let member = self.stack.pop().expect("node's own SCC root is on the stack");
+                    self.on_stack.remove(&member);
+                    let is_root = &member == node;
+                    component.push(member);
+                    if is_root {
+                        break;
+                    }
+                }
+                self.components.push(component);
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        graph,
+        next_index: 0,
+        stack: vec![],
+        on_stack: HashSet::new(),
+        index: HashMap::new(),
+        lowlink: HashMap::new(),
+        components: vec![],
+    };
+    for node in graph.keys() {
+        if !tarjan.index.contains_key(node) {
+            tarjan.visit(node);
+        }
+    }
+
+    tarjan
+        .components
+        .into_iter()
+        .filter(|component| {
+            component.len() >= 2
+                || graph
+                    .get(&component[0])
+                    .map_or(false, |edges| edges.iter().any(|(_, to)| to == &component[0]))
+        })
+        .collect()
+}
+
+/// Validate that the cross-resource `relations` declared across all loaded resource blocks don't
+/// form a cycle, per the TODO on `implication_body_to_rule_body`: a relation cycle (whether a
+/// single block relating to itself or several blocks relating to each other in a loop) would make
+/// any rewritten rule whose implication traverses an edge inside the cycle directly or mutually
+/// recursive, which can diverge when nothing in the rewrite provides a base case. Stores every SCC
+/// found in `blocks.relation_cycles` regardless, and returns one diagnostic for every implication
+/// that actually closes one of those cycles.
+pub fn check_for_relation_cycles(blocks: &mut ResourceBlocks) -> Vec<PolarError> {
+    let cycles = strongly_connected_components(&relation_graph(blocks));
+    blocks.relation_cycles = cycles.clone();
+    if cycles.is_empty() {
+        return vec![];
+    }
+
+    let mut errors = vec![];
+    for (resource, implications) in &blocks.implications {
+        for implication in implications {
+            let relation = match &implication.body.1 {
+                Some(relation) => relation,
+                None => continue,
+            };
+            let target = match blocks.get_relation_type_in_resource_block(relation, resource) {
+                Ok(target) => target,
+                Err(_) => continue,
+            };
+            let cycle = match cycles
+                .iter()
+                .find(|cycle| cycle.contains(resource) && cycle.contains(target))
+            {
+                Some(cycle) => cycle,
+                None => continue,
+            };
+            let chain: Vec<String> = cycle.iter().map(Term::to_polar).collect();
+            let msg = format!(
+                "Resource block relation cycle detected: {}. The implication `{} if {} on {};` \
+                in the '{}' resource block traverses this cycle, which can make the rewritten \
+                rule non-terminating.",
+                chain.join(" -> "),
+                implication.head.to_polar(),
+                implication.body.0.to_polar(),
+                relation.to_polar(),
+                resource.to_polar(),
+            );
+            let (loc, ranges) = (relation.offset(), vec![]);
+            errors.push(ParseError::ParseSugar { loc, msg, ranges }.into());
+        }
+    }
+    errors
+}
+
 fn check_that_implication_heads_are_declared_locally(
     implications: &[Implication],
     declarations: &Declarations,
@@ -624,20 +1361,147 @@ fn check_that_implication_heads_are_declared_locally(
     let mut errors = vec![];
     for Implication { head, .. } in implications {
         if !declarations.contains_key(head) {
-            let msg = format!(
-                "Undeclared term {} referenced in rule in '{}' resource block. \
-                Did you mean to declare it as a role, permission, or relation?",
-                head.to_polar(),
-                resource
+            errors.push(
+                ValidationError::UndeclaredTerm {
+                    term: head.clone(),
+                    resource: resource.clone(),
+                }
+                .into(),
             );
-            let (loc, ranges) = (head.offset(), vec![]);
-            let error = ParseError::ParseSugar { loc, msg, ranges };
-            errors.push(error.into());
         }
     }
     errors
 }
 
+/// Does this resource block's own implications, taken together, imply anything circularly -- e.g.
+/// `"writer" if "writer";` or `"admin" if "reader"; "writer" if "admin"; "reader" if "writer";`?
+/// Builds a `head -> implier` dependency graph (a relation-qualified body, e.g. `... on "parent"`,
+/// targets a node keyed by the relation's declared type rather than this block's own resource, so a
+/// chain that crosses into another resource block only closes into a cycle here if the author
+/// routed it back into this one) and runs a three-color DFS over it: a node is pushed gray on
+/// entry and blackened once fully explored, so reaching a gray node again is a back edge, and
+/// therefore a cycle. The gray-stack slice from the revisited node onward reconstructs the
+/// offending chain for the error message. Self-loops are just the one-node case. Cross-resource
+/// *relation* cycles proper (where the chain genuinely does cross into another block's own
+/// implications) are instead caught once every block has loaded, by `check_for_relation_cycles`.
+fn check_for_circular_implications(
+    resource: &Term,
+    implications: &[Implication],
+    declarations: &Declarations,
+) -> Vec<PolarError> {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum Color {
+        Gray,
+        Black,
+    }
+
+    fn target_node(
+        resource: &Term,
+        implication: &Implication,
+        declarations: &Declarations,
+    ) -> (Term, Term) {
+        match &implication.body.1 {
+            None => (resource.clone(), implication.body.0.clone()),
+            Some(relation) => {
+                let target_resource = declarations
+                    .get(relation)
+                    .and_then(|declaration| declaration.as_relation_type().ok())
+                    .cloned()
+                    .unwrap_or_else(|| resource.clone());
+                (target_resource, implication.body.0.clone())
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn visit(
+        node: (Term, Term),
+        resource: &Term,
+        graph: &HashMap<(Term, Term), Vec<&Implication>>,
+        declarations: &Declarations,
+        color: &mut HashMap<(Term, Term), Color>,
+        stack: &mut Vec<(Term, Term, Implication)>,
+        errors: &mut Vec<PolarError>,
+    ) {
+        color.insert(node.clone(), Color::Gray);
+        for implication in graph.get(&node).into_iter().flatten() {
+            let target = target_node(resource, implication, declarations);
+            stack.push((node.clone(), target.clone(), (*implication).clone()));
+            match color.get(&target) {
+                Some(Color::Gray) => {
+                    let cycle_start = stack
+                        .iter()
+                        .position(|(from, _, _)| from == &target)
+                        .unwrap_or(0);
+                    let cycle = &stack[cycle_start..];
+                    let mut names: Vec<String> = cycle
+                        .iter()
+                        .map(|(_, _, implication)| implication.head.to_polar())
+                        .collect();
+                    names.push(cycle.last().unwrap().2.body.0.to_polar());
+                    let msg = format!(
+                        "Circular implication detected in resource block: {}. This would make the \
+                        rewritten rules recurse without ever terminating.",
+                        names.join(" if ")
+                    );
+                    let loc = cycle[0].2.head.offset();
+                    errors.push(ParseError::ParseSugar { loc, msg, ranges: vec![] }.into());
+                }
+                Some(Color::Black) => (),
+                None => {
+                    visit(
+                        target,
+                        resource,
+                        graph,
+                        declarations,
+                        color,
+                        stack,
+                        errors,
+                    );
+                }
+            }
+            stack.pop();
+        }
+        color.insert(node, Color::Black);
+    }
+
+    let mut graph: HashMap<(Term, Term), Vec<&Implication>> = HashMap::new();
+    for implication in implications {
+        let from = (resource.clone(), implication.head.clone());
+        graph.entry(from).or_default().push(implication);
+    }
+
+    let mut color = HashMap::new();
+    let mut errors = vec![];
+    let mut stack = vec![];
+    for implication in implications {
+        let node = (resource.clone(), implication.head.clone());
+        if !color.contains_key(&node) {
+            visit(
+                node,
+                resource,
+                &graph,
+                declarations,
+                &mut color,
+                &mut stack,
+                &mut errors,
+            );
+        }
+    }
+    errors
+}
+
+/// Combine multiple resource-block validation errors into a single error that reports all of
+/// them, each retaining its own `loc`/`ranges` so a caller can still point at every individual
+/// mistake instead of only the first one found. A lone error is passed through unchanged so it
+/// doesn't get wrapped in a pointless batch-of-one.
+pub(crate) fn combine_errors(mut errors: Vec<PolarError>) -> PolarError {
+    if errors.len() == 1 {
+        return errors.pop().unwrap();
+    }
+    ParseError::Many(errors).into()
+}
+
 impl ResourceBlock {
     // TODO(gj): Add 'includes' feature to ensure we have a clean hook for validation _after_ all
     // Polar rules are loaded.
@@ -654,23 +1518,80 @@ impl ResourceBlock {
             permissions,
             relations,
             implications,
+            extends,
         } = self;
 
-        let declarations = index_declarations(roles, permissions, relations, &resource)?;
+        let mut roles = roles;
+        let mut permissions = permissions;
+        for name in &extends {
+            let set_name = match name.value().as_symbol() {
+                Ok(set_name) => set_name,
+                Err(_) => continue,
+            };
+            match kb.resource_blocks.declaration_sets.get(set_name).cloned() {
+                Some(set) => {
+                    errors.extend(
+                        check_for_duplicates_with_extended_set(
+                            "roles",
+                            roles.as_ref(),
+                            set.roles.as_ref(),
+                            &resource,
+                        )
+                        .err(),
+                    );
+                    errors.extend(
+                        check_for_duplicates_with_extended_set(
+                            "permissions",
+                            permissions.as_ref(),
+                            set.permissions.as_ref(),
+                            &resource,
+                        )
+                        .err(),
+                    );
+                    roles = merge_declaration_lists(roles, set.roles.as_ref())?;
+                    permissions = merge_declaration_lists(permissions, set.permissions.as_ref())?;
+                }
+                None => {
+                    let (loc, ranges) = (name.offset(), vec![]);
+                    let msg = format!(
+                        "Undeclared declaration set '{}' referenced via 'extends'.",
+                        name.to_polar()
+                    );
+                    errors.push(ParseError::ParseSugar { loc, msg, ranges }.into());
+                }
+            }
+        }
 
-        errors.append(&mut check_that_implication_heads_are_declared_locally(
-            &implications,
-            &declarations,
-            &resource,
-        ));
+        // The checks below all depend on `declarations`, so a failure here means we can't run them
+        // -- but we still want to report every _other_ error we've already collected above instead
+        // of losing them to a fail-fast `?`.
+        let declarations = match index_declarations(roles, permissions, relations, &resource) {
+            Ok(declarations) => Some(declarations),
+            Err(error) => {
+                errors.push(error);
+                None
+            }
+        };
+
+        if let Some(declarations) = &declarations {
+            errors.append(&mut check_that_implication_heads_are_declared_locally(
+                &implications,
+                declarations,
+                &resource,
+            ));
+            errors.append(&mut check_for_circular_implications(
+                &resource,
+                &implications,
+                declarations,
+            ));
+        }
 
-        // TODO(gj): Emit all errors instead of just the first.
         if !errors.is_empty() {
-            return Err(errors[0].clone());
+            return Err(combine_errors(errors));
         }
 
         kb.resource_blocks
-            .add(entity_type, resource, declarations, implications);
+            .add(entity_type, resource, declarations.expect("checked above"), implications);
 
         Ok(())
     }
@@ -687,16 +1608,41 @@ mod tests {
     use crate::polar::Polar;
 
     #[track_caller]
+    /// Assert that `expected` shows up somewhere in `policy`'s load error -- either as the whole
+    /// message of a single `ParseSugar` error, or as one of the messages batched into a `Many` when
+    /// `policy` trips more than one validation check at once.
     fn expect_error(p: &Polar, policy: &str, expected: &str) {
-        let msg = match p.load_str(policy).unwrap_err() {
+        let messages = match p.load_str(policy).unwrap_err() {
             error::PolarError {
                 kind: error::ErrorKind::Parse(error::ParseError::ParseSugar { msg, .. }),
                 ..
-            } => msg,
+            } => vec![msg],
+            error::PolarError {
+                kind: error::ErrorKind::Parse(error::ParseError::Many(errors)),
+                ..
+            } => errors.into_iter().map(|error| error.to_string()).collect(),
+            error::PolarError {
+                kind: error::ErrorKind::Validation(error),
+                ..
+            } => vec![error.to_string()],
             _ => panic!(),
         };
 
-        assert!(msg.contains(expected));
+        assert!(messages.iter().any(|msg| msg.contains(expected)));
+    }
+
+    #[track_caller]
+    /// Like `expect_error`, but for a `PolarError` returned directly from a unit (rather than
+    /// `Polar::load_str`) call -- e.g. `ResourceBlock::add_to_kb` invoked straight off a
+    /// hand-constructed `ResourceBlock`.
+    fn assert_error_contains(error: &error::PolarError, expected: &str) {
+        let messages: Vec<String> = match &error.kind {
+            error::ErrorKind::Parse(error::ParseError::Many(errors)) => {
+                errors.iter().map(ToString::to_string).collect()
+            }
+            _ => vec![error.to_string()],
+        };
+        assert!(messages.iter().any(|msg| msg.contains(expected)), "{:?}", messages);
     }
 
     #[test]
@@ -839,6 +1785,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_resource_block_accumulates_multiple_errors() {
+        let p = Polar::new();
+        p.register_constant(sym!("Org"), term!("unimportant"));
+        let policy = r#"resource Org {}
+resource Org { "member" if "owner"; }"#;
+        expect_error(&p, policy, "Duplicate declaration of 'Org' resource block.");
+        expect_error(
+            &p,
+            policy,
+            r#"Undeclared term "member" referenced in rule in 'Org' resource block."#,
+        );
+    }
+
     #[test]
     fn test_resource_block_with_undeclared_local_implication_head() {
         let p = Polar::new();
@@ -909,30 +1869,39 @@ mod tests {
     }
 
     #[test]
-    #[ignore = "not yet implemented"]
     fn test_resource_block_with_circular_implications() {
         let p = Polar::new();
         p.register_constant(sym!("Repo"), term!("unimportant"));
-        let policy = r#"resource Repo {
-            roles = [ "writer" ];
-            "writer" if "writer";
-        }"#;
-        panic!("{}", p.load_str(policy).unwrap_err());
-
-        // let policy = r#"resource Repo {
-        //     roles = [ "writer", "reader" ];
-        //     "writer" if "reader";
-        //     "reader" if "writer";
-        // }"#;
-        // panic!("{}", p.load_str(policy).unwrap_err());
-        //
-        // let policy = r#"resource Repo {
-        //     roles = [ "writer", "reader", "admin" ];
-        //     "admin" if "reader";
-        //     "writer" if "admin";
-        //     "reader" if "writer";
-        // }"#;
-        // panic!("{}", p.load_str(policy).unwrap_err());
+
+        expect_error(
+            &p,
+            r#"resource Repo {
+                roles = [ "writer" ];
+                "writer" if "writer";
+            }"#,
+            r#"Circular implication detected in resource block: "writer" if "writer""#,
+        );
+
+        expect_error(
+            &p,
+            r#"resource Repo {
+                roles = [ "writer", "reader" ];
+                "writer" if "reader";
+                "reader" if "writer";
+            }"#,
+            r#"Circular implication detected in resource block: "writer" if "reader" if "writer""#,
+        );
+
+        expect_error(
+            &p,
+            r#"resource Repo {
+                roles = [ "writer", "reader", "admin" ];
+                "admin" if "reader";
+                "writer" if "admin";
+                "reader" if "writer";
+            }"#,
+            r#"Circular implication detected in resource block:"#,
+        );
     }
 
     #[test]
@@ -1184,4 +2153,581 @@ mod tests {
         )
         .unwrap();
     }
+
+    #[test]
+    fn test_register_union_types_noop_when_no_resource_blocks_loaded() {
+        let mut kb = KnowledgeBase::new();
+        register_union_types(&mut kb, &HashSet::new(), &HashSet::new()).unwrap();
+        assert!(!kb.is_constant(&Symbol::new(ACTOR_UNION_NAME)));
+        assert!(!kb.is_constant(&Symbol::new(RESOURCE_UNION_NAME)));
+    }
+
+    #[test]
+    fn test_register_union_types_requires_both_an_actor_and_a_resource() {
+        let mut kb = KnowledgeBase::new();
+        let mut actors = HashSet::new();
+        actors.insert(term!(sym!("User")));
+
+        let error = register_union_types(&mut kb, &actors, &HashSet::new()).unwrap_err();
+        assert!(error.to_string().contains(
+            "no resource block declares an actor (e.g. `actor User {}`) or no resource block \
+            declares a non-actor resource"
+        ));
+    }
+
+    #[test]
+    fn test_register_union_type_makes_every_member_a_subclass_of_the_union() {
+        use crate::kb::RuleParamMatch;
+
+        let mut kb = KnowledgeBase::new();
+        kb.constant(
+            sym!("Repo"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 1,
+                constructor: None,
+                repr: None,
+            })),
+        );
+        kb.add_mro(sym!("Repo"), vec![1]).unwrap();
+
+        let mut resources = HashSet::new();
+        resources.insert(term!(sym!("Repo")));
+        register_union_type(&mut kb, RESOURCE_UNION_NAME, &resources).unwrap();
+
+        // `register_union_type` extends `Repo`'s MRO with the synthesized union, so a rule
+        // specializing on the concrete `Repo` class should satisfy a prototype specializing on the
+        // union.
+        assert!(matches!(
+            kb.rule_params_match(
+                &rule!("f", ["x"; instance!(sym!("Repo"))]),
+                &rule!("f", ["x"; instance!(Symbol::new(RESOURCE_UNION_NAME))])
+            ),
+            Ok(RuleParamMatch::True)
+        ));
+    }
+
+    fn mutually_related_repo_and_org() -> ResourceBlocks {
+        let repo_resource = term!(sym!("Repo"));
+        let repo_relations = term!(btreemap! { sym!("parent") => term!(sym!("Org")) });
+        let repo_declarations =
+            index_declarations(Some(term!(["writer"])), None, Some(repo_relations), &repo_resource)
+                .unwrap();
+
+        let org_resource = term!(sym!("Org"));
+        let org_relations = term!(btreemap! { sym!("child") => term!(sym!("Repo")) });
+        let org_declarations =
+            index_declarations(Some(term!(["owner"])), None, Some(org_relations), &org_resource)
+                .unwrap();
+
+        let mut blocks = ResourceBlocks::new();
+        blocks.add(
+            EntityType::Resource,
+            repo_resource,
+            repo_declarations,
+            vec![Implication {
+                head: term!("writer"),
+                body: (term!("owner"), Some(term!("parent"))),
+            }],
+        );
+        blocks.add(EntityType::Resource, org_resource, org_declarations, vec![]);
+        blocks
+    }
+
+    #[test]
+    fn test_check_for_relation_cycles_detects_cross_resource_cycle() {
+        let mut blocks = mutually_related_repo_and_org();
+
+        let errors = check_for_relation_cycles(&mut blocks);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0]
+            .to_string()
+            .contains("Resource block relation cycle detected:"));
+        assert_eq!(blocks.relation_cycles.len(), 1);
+        assert_eq!(blocks.relation_cycles[0].len(), 2);
+    }
+
+    #[test]
+    fn test_check_for_relation_cycles_no_false_positive_for_acyclic_relations() {
+        let repo_resource = term!(sym!("Repo"));
+        let repo_relations = term!(btreemap! { sym!("parent") => term!(sym!("Org")) });
+        let repo_declarations =
+            index_declarations(Some(term!(["writer"])), None, Some(repo_relations), &repo_resource)
+                .unwrap();
+
+        let org_resource = term!(sym!("Org"));
+        let org_declarations =
+            index_declarations(Some(term!(["owner"])), None, None, &org_resource).unwrap();
+
+        let mut blocks = ResourceBlocks::new();
+        blocks.add(
+            EntityType::Resource,
+            repo_resource,
+            repo_declarations,
+            vec![Implication {
+                head: term!("writer"),
+                body: (term!("owner"), Some(term!("parent"))),
+            }],
+        );
+        blocks.add(EntityType::Resource, org_resource, org_declarations, vec![]);
+
+        assert!(check_for_relation_cycles(&mut blocks).is_empty());
+        assert!(blocks.relation_cycles.is_empty());
+    }
+
+    #[test]
+    fn test_check_declaration_exhaustiveness_flags_unused_declarations() {
+        let mut kb = KnowledgeBase::new();
+        let resource = term!(sym!("Repo"));
+        let roles = term!(["owner"]);
+        let permissions = term!(["delete"]);
+        let relations = term!(btreemap! { sym!("parent") => term!(sym!("Org")) });
+        let declarations =
+            index_declarations(Some(roles), Some(permissions), Some(relations), &resource).unwrap();
+        kb.resource_blocks
+            .add(EntityType::Resource, resource, declarations, vec![]);
+
+        let messages: Vec<String> = check_declaration_exhaustiveness(&kb)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("is never granted") && m.contains("\"delete\"")));
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("is never used as an implier") && m.contains("\"owner\"")));
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("has no corresponding `has_relation/3` rule")));
+    }
+
+    #[test]
+    fn test_check_declaration_exhaustiveness_no_false_positive_when_everything_is_used() {
+        let mut kb = KnowledgeBase::new();
+        let resource = term!(sym!("Repo"));
+        let roles = term!(["owner"]);
+        let permissions = term!(["delete"]);
+        let relations = term!(btreemap! { sym!("parent") => term!(sym!("Org")) });
+        let declarations =
+            index_declarations(Some(roles), Some(permissions), Some(relations), &resource).unwrap();
+        let implications = vec![Implication {
+            head: term!("delete"),
+            body: (term!("owner"), None),
+        }];
+        kb.resource_blocks
+            .add(EntityType::Resource, resource, declarations, implications);
+        kb.add_rule(rule!("has_relation", [sym!("a"), sym!("b"), sym!("c")]));
+
+        assert!(check_declaration_exhaustiveness(&kb).is_empty());
+    }
+
+    #[test]
+    fn test_check_all_relation_types_have_been_registered_flags_unregistered_type() {
+        let mut kb = KnowledgeBase::new();
+        let resource = term!(sym!("Repo"));
+        let relations = term!(btreemap! { sym!("parent") => term!(sym!("Org")) });
+        let declarations = index_declarations(None, None, Some(relations), &resource).unwrap();
+        kb.resource_blocks
+            .add(EntityType::Resource, resource, declarations, vec![]);
+
+        let errors = check_all_relation_types_have_been_registered(&kb);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().contains("must be registered as a class"));
+    }
+
+    #[test]
+    fn test_check_all_relation_types_have_been_registered_no_false_positive_when_registered() {
+        let mut kb = KnowledgeBase::new();
+        kb.constant(sym!("Org"), term!("unimportant"));
+        let resource = term!(sym!("Repo"));
+        let relations = term!(btreemap! { sym!("parent") => term!(sym!("Org")) });
+        let declarations = index_declarations(None, None, Some(relations), &resource).unwrap();
+        kb.resource_blocks
+            .add(EntityType::Resource, resource, declarations, vec![]);
+
+        assert!(check_all_relation_types_have_been_registered(&kb).is_empty());
+    }
+
+    #[test]
+    fn test_add_to_kb_merges_extends_declaration_set_into_implications() {
+        let mut kb = KnowledgeBase::new();
+        kb.constant(sym!("Repo"), term!("unimportant"));
+        kb.resource_blocks.declare_set(
+            sym!("common_roles"),
+            DeclarationSet {
+                roles: Some(term!(["owner"])),
+                permissions: None,
+            },
+        );
+
+        let block = ResourceBlock {
+            entity_type: EntityType::Resource,
+            resource: term!(sym!("Repo")),
+            roles: Some(term!(["writer"])),
+            permissions: None,
+            relations: None,
+            implications: vec![Implication {
+                head: term!("writer"),
+                body: (term!("owner"), None),
+            }],
+            extends: vec![term!(sym!("common_roles"))],
+        };
+        block.add_to_kb(&mut kb).unwrap();
+
+        // The imported "owner" role was merged in as if it had been declared inline, so the
+        // implication referencing it validates and rewrites normally.
+        let expansions = kb.resource_blocks.desugar().unwrap();
+        assert_eq!(expansions.len(), 1);
+        assert_eq!(expansions[0].rules.len(), 1);
+        assert_eq!(
+            expansions[0].rules[0].to_polar(),
+            r#"has_role(actor: Actor{}, "writer", repo: Repo{}) if has_role(actor, "owner", repo);"#
+        );
+    }
+
+    #[test]
+    fn test_add_to_kb_errors_on_undeclared_extends_set() {
+        let mut kb = KnowledgeBase::new();
+        kb.constant(sym!("Repo"), term!("unimportant"));
+
+        let block = ResourceBlock {
+            entity_type: EntityType::Resource,
+            resource: term!(sym!("Repo")),
+            roles: Some(term!(["writer"])),
+            permissions: None,
+            relations: None,
+            implications: vec![],
+            extends: vec![term!(sym!("nonexistent"))],
+        };
+
+        let error = block.add_to_kb(&mut kb).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Undeclared declaration set 'nonexistent' referenced via 'extends'."));
+    }
+
+    #[test]
+    fn test_add_to_kb_errors_on_duplicate_declaration_with_extended_set() {
+        let mut kb = KnowledgeBase::new();
+        kb.constant(sym!("Repo"), term!("unimportant"));
+        kb.resource_blocks.declare_set(
+            sym!("common_roles"),
+            DeclarationSet {
+                roles: Some(term!(["writer"])),
+                permissions: None,
+            },
+        );
+
+        let block = ResourceBlock {
+            entity_type: EntityType::Resource,
+            resource: term!(sym!("Repo")),
+            roles: Some(term!(["writer"])),
+            permissions: None,
+            relations: None,
+            implications: vec![],
+            extends: vec![term!(sym!("common_roles"))],
+        };
+
+        let error = block.add_to_kb(&mut kb).unwrap_err();
+        assert_error_contains(&error, "already pulled in via 'extends'");
+    }
+
+    #[test]
+    fn test_desugar_returns_one_expansion_per_resource_with_its_rewritten_rules() {
+        let resource = term!(sym!("Org"));
+        let declarations =
+            index_declarations(Some(term!(["owner", "member"])), None, None, &resource).unwrap();
+        let mut blocks = ResourceBlocks::new();
+        blocks.add(
+            EntityType::Resource,
+            resource.clone(),
+            declarations,
+            vec![Implication {
+                head: term!("member"),
+                body: (term!("owner"), None),
+            }],
+        );
+
+        let expansions = blocks.desugar().unwrap();
+        assert_eq!(expansions.len(), 1);
+        assert_eq!(expansions[0].resource, resource);
+        assert_eq!(expansions[0].rules.len(), 1);
+        assert_eq!(
+            expansions[0].rules[0].to_polar(),
+            r#"has_role(actor: Actor{}, "member", org: Org{}) if has_role(actor, "owner", org);"#
+        );
+    }
+
+    #[test]
+    fn test_desugar_expansion_has_no_rules_when_block_has_no_implications() {
+        let resource = term!(sym!("Org"));
+        let declarations = index_declarations(Some(term!(["owner"])), None, None, &resource).unwrap();
+        let mut blocks = ResourceBlocks::new();
+        blocks.add(EntityType::Resource, resource, declarations, vec![]);
+
+        let expansions = blocks.desugar().unwrap();
+        assert_eq!(expansions.len(), 1);
+        assert!(expansions[0].rules.is_empty());
+    }
+
+    #[test]
+    fn test_validation_error_loc_points_at_the_offending_term() {
+        let repo = term!(sym!("Repo"));
+        let term = term!("admin");
+        let relation = term!(sym!("parent"));
+
+        assert_eq!(
+            ValidationError::UnregisteredClass { name: repo.clone() }.loc(),
+            repo.offset()
+        );
+        assert_eq!(
+            ValidationError::UndeclaredTerm {
+                term: term.clone(),
+                resource: repo.clone()
+            }
+            .loc(),
+            term.offset()
+        );
+        assert_eq!(
+            ValidationError::DuplicateDeclaration {
+                term: term.clone(),
+                resource: repo.clone(),
+                kind: "roles",
+                extended_from: None,
+            }
+            .loc(),
+            term.offset()
+        );
+        assert_eq!(
+            ValidationError::ClashingDeclaration {
+                term: term.clone(),
+                resource: repo.clone(),
+                this_kind: "permission",
+                prior_kind: "role",
+            }
+            .loc(),
+            term.offset()
+        );
+        assert_eq!(
+            ValidationError::UnregisteredRelationType {
+                relation: relation.clone(),
+                kind: repo,
+            }
+            .loc(),
+            relation.offset()
+        );
+    }
+
+    #[test]
+    fn test_validation_error_ranges_empty_unless_duplicate_via_extends() {
+        let repo = term!(sym!("Repo"));
+        let term = term!("admin");
+
+        assert!(ValidationError::UnregisteredClass { name: repo.clone() }
+            .ranges()
+            .is_empty());
+        assert!(ValidationError::DuplicateDeclaration {
+            term: term.clone(),
+            resource: repo.clone(),
+            kind: "roles",
+            extended_from: None,
+        }
+        .ranges()
+        .is_empty());
+        assert!(ValidationError::ClashingDeclaration {
+            term,
+            resource: repo,
+            this_kind: "permission",
+            prior_kind: "role",
+        }
+        .ranges()
+        .is_empty());
+    }
+
+    #[test]
+    fn test_validation_error_display_messages() {
+        let repo = term!(sym!("Repo"));
+        let term = term!("admin");
+
+        assert_eq!(
+            ValidationError::UnregisteredClass { name: repo.clone() }.to_string(),
+            r#"Invalid resource block 'Repo' -- 'Repo' must be a registered class."#
+        );
+        assert_eq!(
+            ValidationError::DuplicateDeclaration {
+                term: term.clone(),
+                resource: repo.clone(),
+                kind: "roles",
+                extended_from: Some(term!("owner")),
+            }
+            .to_string(),
+            r#"Repo: Duplicate declaration of "admin" in the roles list -- it's already pulled in via 'extends'."#
+        );
+        assert_eq!(
+            ValidationError::DuplicateDeclaration {
+                term: term.clone(),
+                resource: repo.clone(),
+                kind: "roles",
+                extended_from: None,
+            }
+            .to_string(),
+            r#"Repo: Duplicate declaration of "admin" in the roles list."#
+        );
+        assert_eq!(
+            ValidationError::ClashingDeclaration {
+                term,
+                resource: repo,
+                this_kind: "permission",
+                prior_kind: "role",
+            }
+            .to_string(),
+            r#"Repo: "admin" declared as a permission but it was previously declared as a role."#
+        );
+    }
+
+    #[test]
+    fn test_materialize_closure_includes_transitive_grants_across_a_relation() {
+        let repo_resource = term!(sym!("Repo"));
+        let repo_relations = term!(btreemap! { sym!("parent") => term!(sym!("Org")) });
+        let repo_declarations =
+            index_declarations(Some(term!(["writer"])), None, Some(repo_relations), &repo_resource)
+                .unwrap();
+
+        let org_resource = term!(sym!("Org"));
+        let org_declarations =
+            index_declarations(Some(term!(["owner", "member"])), None, None, &org_resource).unwrap();
+
+        let mut blocks = ResourceBlocks::new();
+        blocks.add(
+            EntityType::Resource,
+            repo_resource.clone(),
+            repo_declarations,
+            vec![Implication {
+                head: term!("writer"),
+                body: (term!("owner"), Some(term!("parent"))),
+            }],
+        );
+        blocks.add(
+            EntityType::Resource,
+            org_resource.clone(),
+            org_declarations,
+            vec![Implication {
+                head: term!("owner"),
+                body: (term!("member"), None),
+            }],
+        );
+
+        blocks.materialize_closure();
+
+        let grants = blocks
+            .grants_for(&repo_resource, &term!("writer"))
+            .expect("writer should have a materialized closure");
+        assert_eq!(grants.len(), 2);
+        assert!(grants.contains(&GrantEdge {
+            resource: org_resource.clone(),
+            term: term!("owner"),
+            relation: Some(term!("parent")),
+        }));
+        assert!(grants.contains(&GrantEdge {
+            resource: org_resource,
+            term: term!("member"),
+            relation: None,
+        }));
+    }
+
+    #[test]
+    fn test_grants_for_returns_none_before_the_closure_has_been_materialized() {
+        let resource = term!(sym!("Org"));
+        let declarations = index_declarations(Some(term!(["owner"])), None, None, &resource).unwrap();
+        let mut blocks = ResourceBlocks::new();
+        blocks.add(
+            EntityType::Resource,
+            resource.clone(),
+            declarations,
+            vec![Implication {
+                head: term!("owner"),
+                body: (term!("member"), None),
+            }],
+        );
+
+        assert!(blocks.grants_for(&resource, &term!("owner")).is_none());
+    }
+
+    #[test]
+    fn test_take_closure_leaves_none_behind() {
+        let resource = term!(sym!("Org"));
+        let declarations = index_declarations(Some(term!(["owner"])), None, None, &resource).unwrap();
+        let mut blocks = ResourceBlocks::new();
+        blocks.add(
+            EntityType::Resource,
+            resource.clone(),
+            declarations,
+            vec![Implication {
+                head: term!("owner"),
+                body: (term!("member"), None),
+            }],
+        );
+        blocks.materialize_closure();
+
+        let taken = blocks.take_closure();
+        assert!(taken.contains_key(&(resource.clone(), term!("owner"))));
+        assert!(blocks.grants_for(&resource, &term!("owner")).is_none());
+    }
+
+    #[test]
+    fn test_dump_load_round_trip() {
+        let resource = term!(sym!("Org"));
+        let declarations = index_declarations(Some(term!(["owner"])), None, None, &resource).unwrap();
+        let mut blocks = ResourceBlocks::new();
+        blocks.add(
+            EntityType::Resource,
+            resource.clone(),
+            declarations,
+            vec![Implication {
+                head: term!("owner"),
+                body: (term!("owner"), None),
+            }],
+        );
+
+        let bytes = blocks.dump().unwrap();
+        let loaded = ResourceBlocks::load(&bytes).unwrap();
+
+        assert!(loaded.resources.contains(&resource));
+        assert_eq!(loaded.implications.get(&resource).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_load_rejects_a_different_format_version() {
+        let blocks = ResourceBlocks::new();
+        let payload = bincode::serialize(&blocks).unwrap();
+        let cache = ResourceBlocksCache {
+            format_version: RESOURCE_BLOCKS_CACHE_FORMAT_VERSION + 1,
+            hash: ResourceBlocks::hash_bytes(&payload),
+            payload,
+        };
+        let bytes = bincode::serialize(&cache).unwrap();
+
+        let error = ResourceBlocks::load(&bytes).unwrap_err();
+        assert!(error.to_string().contains("different format version"));
+    }
+
+    #[test]
+    fn test_load_rejects_a_payload_that_fails_its_integrity_check() {
+        let blocks = ResourceBlocks::new();
+        let payload = bincode::serialize(&blocks).unwrap();
+        let cache = ResourceBlocksCache {
+            format_version: RESOURCE_BLOCKS_CACHE_FORMAT_VERSION,
+            hash: ResourceBlocks::hash_bytes(&payload) + 1,
+            payload,
+        };
+        let bytes = bincode::serialize(&cache).unwrap();
+
+        let error = ResourceBlocks::load(&bytes).unwrap_err();
+        assert!(error.to_string().contains("integrity check"));
+    }
+
+    #[test]
+    fn test_load_rejects_corrupt_bytes() {
+        assert!(ResourceBlocks::load(b"not a valid cache blob").is_err());
+    }
 }